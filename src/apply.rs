@@ -0,0 +1,405 @@
+//! # apply
+//! Creates and updates entries described by a manifest file. Manifest can be written in TOML, YAML or JSON.
+//!
+//! Each entry of the `entries` array has a `source` and the options without the leading dashes, the same as the
+//! config file. Options of the manifest root are used by all entries. Entries can also have `locales` with the
+//! localized keys and `actions` that are written as the `[Desktop Action id]` groups:
+//! ```toml
+//! out = "~/.local/share/applications"
+//!
+//! [[entries]]
+//! source = "/opt/editor/editor"
+//! name = "Editor"
+//! categories = ["Development", "TextEditor"]
+//! locales.de = { name = "Editor", comment = "Texteditor" }
+//! actions = [{ id = "new-window", name = "New Window", exec = "/opt/editor/editor", args = "--new-window" }]
+//! ```
+//! Files are written only if their content changes. Existing files are replaced only if they were created by mkdsk.
+
+use std::{fs, path::Path};
+
+use path_absolutize::Absolutize;
+use toml::{Table, Value};
+
+use crate::config::{not_overridden, option_value, table_options};
+use crate::custom_keys::CustomKey;
+use crate::handler::{finalize, install, validate_option, validate_source, Install};
+use crate::keyfile::KeyFile;
+use crate::settings::MANIFEST_OWNERSHIP_KEY;
+use crate::shortcut::{list_value, quote_exec, Shortcut};
+
+/// Array of the entries in the manifest root.
+const ENTRIES_KEY: &str = "entries";
+/// Entry keys that are not options.
+const ENTRY_KEYS: [&str; 3] = ["source", "locales", "actions"];
+
+/// Manifest keys of the localized keys.
+const LOCALIZED_KEYS: [(&str, &str); 5] = [
+    ("name", "Name"),
+    ("generic-name", "GenericName"),
+    ("comment", "Comment"),
+    ("icon", "Icon"),
+    ("keywords", "Keywords"),
+];
+/// Manifest keys of the action keys. Program of the `exec` is quoted, its arguments are written as is.
+const ACTION_KEYS: [(&str, &str); 3] = [("name", "Name"), ("icon", "Icon"), ("exec", "Exec")];
+/// Action key of the program arguments.
+const ACTION_ARGS_KEY: &str = "args";
+
+#[derive(Debug, PartialEq)]
+enum Change {
+    Created,
+    Changed,
+    Unchanged,
+    /// File exists and was not created by mkdsk.
+    Conflict,
+}
+
+/// Creates or updates entries of the manifest at `path` and prints what happened to each of them.
+/// Entries are validated and checked for conflicts before any file is written.
+pub fn apply(path: &Path) -> Result<(), String> {
+    let manifest = read_manifest(path)?;
+    let owner = path.absolutize().map_err(|e| e.to_string())?;
+
+    let defaults = table_options(&manifest, &[ENTRIES_KEY])?;
+    let entries = match manifest.get(ENTRIES_KEY) {
+        Some(Value::Array(val)) => val,
+        _ => return Err(format!("{ENTRIES_KEY} array is missing")),
+    };
+
+    let mut shortcuts: Vec<(Shortcut, Vec<Install>)> = Vec::new();
+    for (i, entry) in entries.iter().enumerate() {
+        let (shortcut, installs) = entry
+            .as_table()
+            .ok_or(String::from("entry must be a table"))
+            .and_then(|entry| new_shortcut(entry, &defaults, &owner))
+            .map_err(|e| format!("entry {}: {e}", i + 1))?;
+
+        if shortcuts
+            .iter()
+            .any(|(j, _)| j.file_path() == shortcut.file_path())
+        {
+            return Err(format!(
+                "entry {}: {} is written by another entry",
+                i + 1,
+                shortcut.file_path().display()
+            ));
+        }
+        shortcuts.push((shortcut, installs));
+    }
+
+    let mut changes = Vec::new();
+    for (shortcut, installs) in shortcuts {
+        let text = shortcut.contents()?;
+        let change = change(fs::read(shortcut.file_path()).ok().as_deref(), &text);
+        changes.push((shortcut.file_path(), text, installs, change));
+    }
+
+    let conflicts: Vec<_> = changes
+        .iter()
+        .filter(|(_, _, _, change)| *change == Change::Conflict)
+        .collect();
+    if !conflicts.is_empty() {
+        for (file_path, _, _, _) in &conflicts {
+            println!("{:<10} {}", "conflict", file_path.display());
+        }
+        return Err(format!(
+            "{} files were not created by mkdsk, nothing is written",
+            conflicts.len()
+        ));
+    }
+
+    for (file_path, text, installs, change) in changes {
+        install(&installs).map_err(|e| format!("{}: {}", file_path.display(), e.message))?;
+        if change != Change::Unchanged {
+            fs::write(&file_path, text).map_err(|e| format!("{}: {e}", file_path.display()))?;
+        }
+
+        let change = match change {
+            Change::Created => "created",
+            Change::Changed => "changed",
+            Change::Unchanged => "unchanged",
+            Change::Conflict => unreachable!(),
+        };
+        println!("{change:<10} {}", file_path.display());
+    }
+
+    Ok(())
+}
+
+/// Returns how the file with the `existing` content is changed by the new `text`.
+/// Files without `MANIFEST_OWNERSHIP_KEY` were not created by `mkdsk apply` and are not replaced.
+fn change(existing: Option<&[u8]>, text: &[u8]) -> Change {
+    match existing {
+        None => Change::Created,
+        Some(val) if val == text => Change::Unchanged,
+        Some(val)
+            if KeyFile::parse(&String::from_utf8_lossy(val))
+                .get("Desktop Entry", MANIFEST_OWNERSHIP_KEY)
+                .is_some() =>
+        {
+            Change::Changed
+        }
+        Some(_) => Change::Conflict,
+    }
+}
+
+/// Parses manifest according to its extension.
+fn read_manifest(path: &Path) -> Result<Table, String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let ext = path
+        .extension()
+        .map(|i| i.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    match ext.as_str() {
+        "toml" => toml::from_str(&text).map_err(|e| e.to_string()),
+        "yaml" | "yml" => serde_yaml_ng::from_str(&text).map_err(|e| e.to_string()),
+        "json" => serde_json::from_str(&text).map_err(|e| e.to_string()),
+        _ => Err(String::from(
+            "manifest must be a .toml, .yaml or .json file",
+        )),
+    }
+}
+
+/// Creates `Shortcut` of the manifest entry and returns it with the changes that must be installed with it.
+/// Entry options take precedence over the `defaults`. Entry is marked as created from the `manifest`.
+fn new_shortcut(
+    entry: &Table,
+    defaults: &[(String, String)],
+    manifest: &Path,
+) -> Result<(Shortcut, Vec<Install>), String> {
+    let source = entry
+        .get("source")
+        .and_then(option_value)
+        .ok_or(String::from("source is missing"))?;
+
+    let mut shortcut = Shortcut::new();
+    validate_source(source, &mut shortcut).map_err(|e| e.message)?;
+    shortcut.set_owner_source(MANIFEST_OWNERSHIP_KEY, manifest.to_path_buf());
+
+    let options = table_options(entry, &ENTRY_KEYS)?;
+    for (option, value) in not_overridden(defaults.to_vec(), &options)
+        .into_iter()
+        .chain(options)
+    {
+        validate_option(&option, &value, &mut shortcut).map_err(|e| e.message)?;
+    }
+
+    if let Some(locales) = entry.get("locales") {
+        let locales = locales
+            .as_table()
+            .ok_or(String::from("locales must be a table"))?;
+
+        for (locale, keys) in locales {
+            let keys = keys
+                .as_table()
+                .ok_or(format!("keys of the {locale} locale must be a table"))?;
+
+            for (key, value) in keys {
+                let name =
+                    key_name(&LOCALIZED_KEYS, key).ok_or(format!("{key} can not be localized"))?;
+                let value = key_value(value).ok_or(format!("invalid value of the {key}"))?;
+
+                shortcut.add_custom_key(CustomKey::parse(&format!("{name}[{locale}]={value}"))?);
+            }
+        }
+    }
+
+    if let Some(actions) = entry.get("actions") {
+        let actions = actions
+            .as_array()
+            .ok_or(String::from("actions must be an array"))?;
+
+        for action in actions {
+            let action = action
+                .as_table()
+                .ok_or(String::from("action must be a table"))?;
+            let id = action
+                .get("id")
+                .and_then(Value::as_str)
+                .filter(|i| !i.is_empty())
+                .filter(|i| i.chars().all(|j| j.is_ascii_alphanumeric() || j == '-'))
+                .ok_or(String::from("action must have a valid id"))?;
+
+            let args = match action.get(ACTION_ARGS_KEY) {
+                Some(val) => Some(
+                    option_value(val).ok_or(format!("invalid value of the {ACTION_ARGS_KEY}"))?,
+                ),
+                None => None,
+            };
+            if args.is_some() && !action.contains_key("exec") {
+                return Err(format!("{ACTION_ARGS_KEY} can only be used with exec"));
+            }
+
+            shortcut.add_actions(vec![id.to_string()]);
+            for (key, value) in action
+                .iter()
+                .filter(|(key, _)| *key != "id" && *key != ACTION_ARGS_KEY)
+            {
+                // Localized keys are written as `name[de]`
+                let (base, locale) = match key.strip_suffix(']').and_then(|i| i.split_once('[')) {
+                    Some((base, locale)) => (base, format!("[{locale}]")),
+                    None => (key.as_str(), String::new()),
+                };
+                let name =
+                    key_name(&ACTION_KEYS, base).ok_or(format!("{key} is not an action key"))?;
+                let mut value = option_value(value).ok_or(format!("invalid value of the {key}"))?;
+                if name == "Exec" {
+                    if !locale.is_empty() {
+                        return Err(format!("{base} can not be localized"));
+                    }
+                    value = quote_exec(&value);
+                    if let Some(args) = &args {
+                        value = format!("{value} {args}");
+                    }
+                }
+
+                shortcut.add_custom_key(CustomKey::parse(&format!(
+                    "[Desktop Action {id}]{name}{locale}={value}"
+                ))?);
+            }
+        }
+    }
+
+    let installs = finalize(&mut shortcut).map_err(|e| e.message)?;
+
+    Ok((shortcut, installs))
+}
+
+/// Returns key name that corresponds to the manifest `key`.
+fn key_name(keys: &[(&str, &'static str)], key: &str) -> Option<&'static str> {
+    keys.iter().find(|(i, _)| *i == key).map(|(_, name)| *name)
+}
+
+/// Returns value of the key. Arrays are written as the lists of strings.
+fn key_value(value: &Value) -> Option<String> {
+    match value {
+        Value::Array(items) => items
+            .iter()
+            .map(option_value)
+            .collect::<Option<Vec<String>>>()
+            .map(|i| list_value(&i)),
+        _ => option_value(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manifest_formats() {
+        let manifests = [
+            (
+                "toml",
+                "out = \"/tmp\"\n[[entries]]\nsource = \"sh\"\nname = \"Shell\"\n",
+            ),
+            (
+                "yaml",
+                "out: /tmp\nentries:\n  - source: sh\n    name: Shell\n",
+            ),
+            (
+                "json",
+                r#"{"out": "/tmp", "entries": [{"source": "sh", "name": "Shell"}]}"#,
+            ),
+        ];
+
+        for (ext, text) in manifests {
+            let path =
+                std::env::temp_dir().join(format!("mkdsk-test-{}.{ext}", std::process::id()));
+            fs::write(&path, text).unwrap();
+            let manifest = read_manifest(&path);
+            fs::remove_file(&path).unwrap();
+
+            let manifest = manifest.unwrap();
+            assert_eq!(manifest["out"].as_str(), Some("/tmp"), "{ext}");
+            let entry = manifest[ENTRIES_KEY][0].as_table().unwrap();
+            assert_eq!(entry["source"].as_str(), Some("sh"), "{ext}");
+            assert_eq!(entry["name"].as_str(), Some("Shell"), "{ext}");
+        }
+
+        let path = std::env::temp_dir().join(format!("mkdsk-test-{}.ini", std::process::id()));
+        fs::write(&path, "").unwrap();
+        assert!(read_manifest(&path).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn entry_keys() {
+        let entry: Table = r#"
+            source = "sh"
+            name = "Shell"
+            locales.de = { name = "Konsole", keywords = ["a;b", "c\\d"] }
+            actions = [
+                { id = "login", name = "Login Shell", "name[de]" = "Anmeldeshell", exec = "/opt/my shell", args = "-l %f" },
+            ]
+        "#
+        .parse()
+        .unwrap();
+
+        let manifest = Path::new("/tmp/manifest.toml");
+        let (shortcut, _) = new_shortcut(&entry, &[], manifest).unwrap();
+        let text = String::from_utf8(shortcut.contents().unwrap()).unwrap();
+
+        for line in [
+            "Name=Shell",
+            "Name[de]=Konsole",
+            r"Keywords[de]=a\;b;c\\d;",
+            "Actions=login;",
+            "X-Mkdsk-Manifest=/tmp/manifest.toml",
+            "[Desktop Action login]",
+            "Name=Login Shell",
+            "Name[de]=Anmeldeshell",
+            "Exec=\"/opt/my shell\" -l %f",
+        ] {
+            assert!(text.lines().any(|i| i == line), "{line} in {text}");
+        }
+
+        let invalid = [
+            "source = \"sh\"\nlocales.de = { exec = \"sh\" }",
+            "source = \"sh\"\nactions = [{ name = \"Login\" }]",
+            "source = \"sh\"\nactions = [{ id = \"login\", name = \"Login\", args = \"-l\" }]",
+            "source = \"sh\"\nactions = [{ id = \"login\", name = \"Login\", \"exec[de]\" = \"sh\" }]",
+            "source = \"sh\"\nactions = [{ id = \"login\", exec = \"sh\" }]",
+            "source = \"/nonexistent/mkdsk\"",
+        ];
+        for entry in invalid {
+            let entry: Table = entry.parse().unwrap();
+            assert!(new_shortcut(&entry, &[], manifest).is_err(), "{entry}");
+        }
+    }
+
+    #[test]
+    fn file_changes() {
+        let text = b"[Desktop Entry]\nName=Shell\nX-Mkdsk-Manifest=/tmp/manifest.toml\n";
+
+        assert_eq!(change(None, text), Change::Created);
+        assert_eq!(change(Some(text), text), Change::Unchanged);
+        assert_eq!(
+            change(
+                Some(b"[Desktop Entry]\nName=Old\nX-Mkdsk-Manifest=/tmp/old.toml\n"),
+                text
+            ),
+            Change::Changed
+        );
+        assert_eq!(
+            change(Some(b"[Desktop Entry]\nName=Shell\n"), text),
+            Change::Conflict
+        );
+        assert_eq!(
+            change(
+                Some(b"[Desktop Entry]\nName=App\nX-Mkdsk-Source=/apps/app\n"),
+                text
+            ),
+            Change::Conflict
+        );
+        assert_eq!(
+            change(
+                Some(b"[Desktop Entry]\nName=Shell\n[Other]\nX-Mkdsk-Manifest=/tmp\n"),
+                text
+            ),
+            Change::Conflict
+        );
+    }
+}
@@ -0,0 +1,24 @@
+//! # bytes
+//! Reads values of the little-endian Windows binary formats.
+
+/// Decodes null-terminated UTF-16 string.
+pub fn utf16_string(data: &[u8]) -> String {
+    let chars: Vec<u16> = data
+        .chunks_exact(2)
+        .map(|i| u16::from_le_bytes([i[0], i[1]]))
+        .take_while(|i| *i != 0)
+        .collect();
+
+    String::from_utf16_lossy(&chars)
+}
+
+pub fn u16_at(data: &[u8], pos: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        data.get(pos..pos + 2)?.try_into().unwrap(),
+    ))
+}
+pub fn u32_at(data: &[u8], pos: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        data.get(pos..pos + 4)?.try_into().unwrap(),
+    ))
+}
@@ -0,0 +1,337 @@
+//! # categories
+//! Validates the `Categories` key against the registered menu categories.
+//!
+//! See: https://specifications.freedesktop.org/menu-spec/latest/category-registry.html
+
+/// Categories that entries should be used with. Entry must contain all categories of one of the alternatives.
+type Related = &'static [&'static [&'static str]];
+
+const NO_RELATED: Related = &[];
+
+/// Main categories. Each entry should contain at least one of them.
+const MAIN_CATEGORIES: &[(&str, Related)] = &[
+    ("AudioVideo", NO_RELATED),
+    ("Audio", &[&["AudioVideo"]]),
+    ("Video", &[&["AudioVideo"]]),
+    ("Development", NO_RELATED),
+    ("Education", NO_RELATED),
+    ("Game", NO_RELATED),
+    ("Graphics", NO_RELATED),
+    ("Network", NO_RELATED),
+    ("Office", NO_RELATED),
+    ("Science", NO_RELATED),
+    ("Settings", NO_RELATED),
+    ("System", NO_RELATED),
+    ("Utility", NO_RELATED),
+];
+
+const EDUCATION_OR_SCIENCE: Related = &[&["Education"], &["Science"]];
+const AUDIO_OR_VIDEO: Related = &[&["Audio"], &["Video"], &["AudioVideo"]];
+
+/// Additional categories and the categories they should be used with.
+const ADDITIONAL_CATEGORIES: &[(&str, Related)] = &[
+    ("Building", &[&["Development"]]),
+    ("Debugger", &[&["Development"]]),
+    ("IDE", &[&["Development"]]),
+    ("GUIDesigner", &[&["Development"]]),
+    ("Profiling", &[&["Development"]]),
+    ("RevisionControl", &[&["Development"]]),
+    ("Translation", &[&["Development"]]),
+    ("Calendar", &[&["Office"]]),
+    ("ContactManagement", &[&["Office"]]),
+    (
+        "Database",
+        &[&["Office"], &["Development"], &["AudioVideo"]],
+    ),
+    ("Dictionary", &[&["Office"], &["TextTools"]]),
+    ("Chart", &[&["Office"]]),
+    ("Email", &[&["Office"], &["Network"]]),
+    ("Finance", &[&["Office"]]),
+    ("FlowChart", &[&["Office"]]),
+    ("PDA", &[&["Office"]]),
+    ("ProjectManagement", &[&["Office"], &["Development"]]),
+    ("Presentation", &[&["Office"]]),
+    ("Spreadsheet", &[&["Office"]]),
+    ("WordProcessor", &[&["Office"]]),
+    ("2DGraphics", &[&["Graphics"]]),
+    ("VectorGraphics", &[&["Graphics", "2DGraphics"]]),
+    ("RasterGraphics", &[&["Graphics", "2DGraphics"]]),
+    ("3DGraphics", &[&["Graphics"]]),
+    ("Scanning", &[&["Graphics"]]),
+    ("OCR", &[&["Graphics", "Scanning"]]),
+    ("Photography", &[&["Graphics"], &["Office"]]),
+    ("Publishing", &[&["Graphics"], &["Office"]]),
+    ("Viewer", &[&["Graphics"], &["Office"]]),
+    ("TextTools", &[&["Utility"]]),
+    ("DesktopSettings", &[&["Settings"]]),
+    ("HardwareSettings", &[&["Settings"]]),
+    ("Printing", &[&["HardwareSettings", "Settings"]]),
+    ("PackageManager", &[&["Settings"]]),
+    ("Dialup", &[&["Network"]]),
+    ("InstantMessaging", &[&["Network"]]),
+    ("Chat", &[&["Network"]]),
+    ("IRCClient", &[&["Network"]]),
+    ("Feed", &[&["Network"]]),
+    ("FileTransfer", &[&["Network"]]),
+    ("HamRadio", &[&["Network"], &["Audio"]]),
+    ("News", &[&["Network"]]),
+    ("P2P", &[&["Network"]]),
+    ("RemoteAccess", &[&["Network"]]),
+    ("Telephony", &[&["Network"]]),
+    ("TelephonyTools", &[&["Utility"]]),
+    ("VideoConference", &[&["Network"]]),
+    ("WebBrowser", &[&["Network"]]),
+    ("WebDevelopment", &[&["Network"], &["Development"]]),
+    ("Midi", &[&["AudioVideo", "Audio"]]),
+    ("Mixer", &[&["AudioVideo", "Audio"]]),
+    ("Sequencer", &[&["AudioVideo", "Audio"]]),
+    ("Tuner", &[&["AudioVideo", "Audio"]]),
+    ("TV", &[&["AudioVideo", "Video"]]),
+    ("AudioVideoEditing", AUDIO_OR_VIDEO),
+    ("Player", AUDIO_OR_VIDEO),
+    ("Recorder", AUDIO_OR_VIDEO),
+    ("DiscBurning", &[&["AudioVideo"]]),
+    ("ActionGame", &[&["Game"]]),
+    ("AdventureGame", &[&["Game"]]),
+    ("ArcadeGame", &[&["Game"]]),
+    ("BoardGame", &[&["Game"]]),
+    ("BlocksGame", &[&["Game"]]),
+    ("CardGame", &[&["Game"]]),
+    ("KidsGame", &[&["Game"]]),
+    ("LogicGame", &[&["Game"]]),
+    ("RolePlaying", &[&["Game"]]),
+    ("Shooter", &[&["Game"]]),
+    ("Simulation", &[&["Game"]]),
+    ("SportsGame", &[&["Game"]]),
+    ("StrategyGame", &[&["Game"]]),
+    ("Art", EDUCATION_OR_SCIENCE),
+    ("Construction", EDUCATION_OR_SCIENCE),
+    ("Music", &[&["AudioVideo"], &["Education"]]),
+    ("Languages", EDUCATION_OR_SCIENCE),
+    ("ArtificialIntelligence", EDUCATION_OR_SCIENCE),
+    ("Astronomy", EDUCATION_OR_SCIENCE),
+    ("Biology", EDUCATION_OR_SCIENCE),
+    ("Chemistry", EDUCATION_OR_SCIENCE),
+    ("ComputerScience", EDUCATION_OR_SCIENCE),
+    ("DataVisualization", EDUCATION_OR_SCIENCE),
+    ("Economy", EDUCATION_OR_SCIENCE),
+    ("Electricity", EDUCATION_OR_SCIENCE),
+    ("Geography", EDUCATION_OR_SCIENCE),
+    ("Geology", EDUCATION_OR_SCIENCE),
+    ("Geoscience", EDUCATION_OR_SCIENCE),
+    ("History", EDUCATION_OR_SCIENCE),
+    ("Humanities", EDUCATION_OR_SCIENCE),
+    ("ImageProcessing", EDUCATION_OR_SCIENCE),
+    ("Literature", EDUCATION_OR_SCIENCE),
+    ("Maps", &[&["Education"], &["Science"], &["Utility"]]),
+    ("Math", EDUCATION_OR_SCIENCE),
+    (
+        "NumericalAnalysis",
+        &[&["Education", "Math"], &["Science", "Math"]],
+    ),
+    ("MedicalSoftware", EDUCATION_OR_SCIENCE),
+    ("Physics", EDUCATION_OR_SCIENCE),
+    ("Robotics", EDUCATION_OR_SCIENCE),
+    (
+        "Spirituality",
+        &[&["Education"], &["Science"], &["Utility"]],
+    ),
+    ("Sports", EDUCATION_OR_SCIENCE),
+    (
+        "ParallelComputing",
+        &[
+            &["Education", "ComputerScience"],
+            &["Science", "ComputerScience"],
+        ],
+    ),
+    ("Amusement", NO_RELATED),
+    ("Archiving", &[&["Utility"]]),
+    ("Compression", &[&["Utility"]]),
+    ("Electronics", NO_RELATED),
+    ("Emulator", &[&["System"], &["Game"]]),
+    ("Engineering", NO_RELATED),
+    ("FileTools", &[&["Utility"], &["System"]]),
+    ("FileManager", &[&["System", "FileTools"]]),
+    ("TerminalEmulator", &[&["System"]]),
+    ("Filesystem", &[&["System"]]),
+    ("Monitor", &[&["System"], &["Network"]]),
+    ("Security", &[&["Settings"], &["System"]]),
+    ("Accessibility", &[&["Settings"], &["Utility"]]),
+    ("Calculator", &[&["Utility"]]),
+    ("Clock", &[&["Utility"]]),
+    ("TextEditor", &[&["Utility"]]),
+    ("Documentation", NO_RELATED),
+    ("Adult", NO_RELATED),
+    ("Core", NO_RELATED),
+    ("KDE", &[&["Qt"]]),
+    ("GNOME", &[&["GTK"]]),
+    ("XFCE", &[&["GTK"]]),
+    ("DDE", &[&["Qt"]]),
+    ("GTK", NO_RELATED),
+    ("Qt", NO_RELATED),
+    ("Motif", NO_RELATED),
+    ("Java", NO_RELATED),
+    ("ConsoleOnly", NO_RELATED),
+];
+
+/// Reserved categories. They may only be used together with `OnlyShownIn`.
+const RESERVED_CATEGORIES: [&str; 4] = ["Screensaver", "TrayIcon", "Applet", "Shell"];
+
+/// Maximum edit distance of the suggested category.
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+/// Validates categories. Categories with the `X-` prefix are not validated.
+/// Returns error for the unknown categories and reserved categories used without `OnlyShownIn`.
+/// Prints warnings if the recommendations of the category registry are not followed.
+pub fn validate_categories(categories: &[String], only_shown_in: bool) -> Result<(), String> {
+    let list: Vec<&str> = categories.iter().map(String::as_str).collect();
+    if list.is_empty() {
+        return Ok(());
+    }
+
+    for category in &list {
+        if let Some(e) = category_error(category, only_shown_in) {
+            return Err(e);
+        }
+
+        let related = match MAIN_CATEGORIES
+            .iter()
+            .chain(ADDITIONAL_CATEGORIES)
+            .find(|(name, _)| name == category)
+        {
+            Some((_, related)) => *related,
+            // Custom and reserved categories
+            None => continue,
+        };
+
+        let has_related = related.is_empty()
+            || related
+                .iter()
+                .any(|i| i.iter().all(|required| list.contains(required)));
+        if !has_related {
+            let alternatives: Vec<String> = related.iter().map(|i| i.join(";")).collect();
+            println!(
+                "mkdsk: warning: category {category} should be used with {}",
+                alternatives.join(" or ")
+            );
+        }
+    }
+
+    if !MAIN_CATEGORIES.iter().any(|(name, _)| list.contains(name)) {
+        let main: Vec<&str> = MAIN_CATEGORIES.iter().map(|(name, _)| *name).collect();
+        println!(
+            "mkdsk: warning: categories should contain one of the main categories: {}",
+            main.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+/// Returns `categories` without the categories that can not be used. Used for the categories read from the source,
+/// which often contain unregistered categories like `Internet`.
+pub fn filter_categories(categories: &[String], only_shown_in: bool) -> Vec<String> {
+    categories
+        .iter()
+        .filter(|i| match category_error(i, only_shown_in) {
+            Some(e) => {
+                println!("mkdsk: warning: {e}, skipping");
+                false
+            }
+            None => true,
+        })
+        .cloned()
+        .collect()
+}
+
+/// Returns error if `category` is not registered or if it is a reserved category used without `OnlyShownIn`.
+fn category_error(category: &str, only_shown_in: bool) -> Option<String> {
+    if category.starts_with("X-")
+        || MAIN_CATEGORIES
+            .iter()
+            .chain(ADDITIONAL_CATEGORIES)
+            .any(|(name, _)| *name == category)
+    {
+        return None;
+    }
+
+    if RESERVED_CATEGORIES.contains(&category) {
+        return match only_shown_in {
+            true => None,
+            false => Some(format!(
+                "reserved category {category} can only be used with OnlyShownIn"
+            )),
+        };
+    }
+
+    Some(match suggest_category(category) {
+        Some(val) => format!("unknown category {category}, did you mean {val}?"),
+        None => format!("unknown category {category}, custom categories must start with X-"),
+    })
+}
+
+/// Returns registered category that is the closest to the misspelled `category`.
+fn suggest_category(category: &str) -> Option<&'static str> {
+    let category = category.to_lowercase();
+
+    MAIN_CATEGORIES
+        .iter()
+        .chain(ADDITIONAL_CATEGORIES)
+        .map(|(name, _)| *name)
+        .chain(RESERVED_CATEGORIES)
+        .map(|name| (edit_distance(&category, &name.to_lowercase()), name))
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| name)
+}
+
+/// Returns Levenshtein distance between two strings.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+
+        for (j, b_char) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = match a_char == *b_char {
+                true => previous,
+                false => 1 + previous.min(row[j]).min(current),
+            };
+            previous = current;
+        }
+    }
+
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(categories: &[&str]) -> Vec<String> {
+        categories.iter().map(|i| i.to_string()).collect()
+    }
+
+    #[test]
+    fn category_validation() {
+        let valid = list(&["Graphics", "2DGraphics", "RasterGraphics"]);
+        assert!(validate_categories(&valid, false).is_ok());
+        assert!(validate_categories(&list(&["Utility", "X-Company-Tools"]), false).is_ok());
+        assert!(validate_categories(&list(&["TrayIcon"]), false).is_err());
+        assert!(validate_categories(&list(&["TrayIcon", "Utility"]), true).is_ok());
+
+        let source = list(&["Network", "Internet", "TrayIcon", "X-Custom"]);
+        assert_eq!(filter_categories(&source, false), ["Network", "X-Custom"]);
+        assert_eq!(
+            filter_categories(&source, true),
+            ["Network", "TrayIcon", "X-Custom"]
+        );
+
+        assert_eq!(suggest_category("Utilty"), Some("Utility"));
+        assert_eq!(suggest_category("texteditor"), Some("TextEditor"));
+        assert_eq!(suggest_category("Nothing"), None);
+    }
+}
@@ -0,0 +1,186 @@
+//! # config
+//! Loads default options from `$XDG_CONFIG_HOME/mkdsk/config.toml`.
+//!
+//! Keys are option names without the leading dashes. Arrays pass each item to the repeatable options. Named profiles
+//! are tables of the `profiles` table and are selected with `--profile`:
+//! ```toml
+//! out = "~/.local/share/applications"
+//! categories = ["Utility"]
+//!
+//! [profiles.games]
+//! categories = ["Game"]
+//! ```
+//! Options provided in the command line take precedence over the profile, profile takes precedence over the config.
+
+use std::{fs, io::ErrorKind};
+
+use toml::{Table, Value};
+
+use crate::handler::{is_option, is_same_option, option_key};
+use crate::settings::CONFIG_FILE;
+use crate::xdg::{config_home, home_dir};
+
+/// Table of the named profiles.
+const PROFILES_KEY: &str = "profiles";
+
+/// Returns options of the config and the `profile` that are not overridden by the command line `options`.
+/// Returns error if the config is invalid or if the `profile` does not exist.
+pub fn config_options(
+    profile: Option<&str>,
+    options: &[(String, String)],
+) -> Result<Vec<(String, String)>, String> {
+    let path = config_home().join(CONFIG_FILE);
+
+    let config = match fs::read_to_string(&path) {
+        Ok(text) => text
+            .parse::<Table>()
+            .map_err(|e| format!("{}: {e}", path.display()))?,
+        Err(e) if e.kind() == ErrorKind::NotFound && profile.is_none() => return Ok(Vec::new()),
+        Err(e) => return Err(format!("{}: {e}", path.display())),
+    };
+
+    let defaults =
+        layered_options(&config, profile).map_err(|e| format!("{}: {e}", path.display()))?;
+
+    Ok(not_overridden(defaults, options))
+}
+
+/// Returns `defaults` that are not set by the `options`. Keyed options like `--set` are merged, only a value with the
+/// same key overrides the default.
+pub fn not_overridden(
+    defaults: Vec<(String, String)>,
+    options: &[(String, String)],
+) -> Vec<(String, String)> {
+    defaults
+        .into_iter()
+        .filter(|(i, v)| {
+            !options
+                .iter()
+                .any(|(j, w)| is_same_option(i, j) && option_key(i, v) == option_key(j, w))
+        })
+        .collect()
+}
+
+/// Returns options of the config followed by the options of the `profile`. Config options that are set by the profile
+/// are skipped.
+fn layered_options(config: &Table, profile: Option<&str>) -> Result<Vec<(String, String)>, String> {
+    let global = table_options(config, &[PROFILES_KEY])?;
+
+    let table = match profile {
+        Some(name) => config
+            .get(PROFILES_KEY)
+            .and_then(|i| i.get(name))
+            .and_then(Value::as_table)
+            .ok_or(format!("profile {name} not found"))?,
+        None => return Ok(global),
+    };
+    let profile = table_options(table, &[])?;
+
+    let mut options = not_overridden(global, &profile);
+    options.extend(profile);
+
+    Ok(options)
+}
+
+/// Converts keys of the `table` into the options and their values. `ignored` keys are skipped.
+pub fn table_options(table: &Table, ignored: &[&str]) -> Result<Vec<(String, String)>, String> {
+    let mut options = Vec::new();
+
+    for (key, value) in table {
+        if ignored.contains(&key.as_str()) {
+            continue;
+        }
+
+        let option = match key.len() {
+            1 => format!("-{key}"),
+            _ => format!("--{key}"),
+        };
+        if !is_option(&option) {
+            return Err(format!("unknown option {key}"));
+        }
+
+        let values = match value {
+            Value::Array(items) => items.iter().collect(),
+            _ => vec![value],
+        };
+        for i in values {
+            let value = option_value(i).ok_or(format!("invalid value of the {key}"))?;
+            options.push((option.clone(), value));
+        }
+    }
+
+    Ok(options)
+}
+
+/// Returns option value of the scalar TOML value. Leading `~/` of the strings is expanded to the home directory.
+pub fn option_value(value: &Value) -> Option<String> {
+    match value {
+        Value::String(val) => match val.strip_prefix("~/") {
+            Some(path) => Some(home_dir().join(path).to_str()?.to_string()),
+            None => Some(val.clone()),
+        },
+        Value::Integer(val) => Some(val.to_string()),
+        Value::Float(val) => Some(val.to_string()),
+        Value::Boolean(val) => Some(val.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_layers() {
+        let config: Table = r#"
+            terminal = true
+            categories = ["Utility", "System"]
+            n = "Name"
+
+            [profiles.games]
+            categories = ["Game"]
+        "#
+        .parse()
+        .unwrap();
+
+        let options = layered_options(&config, Some("games")).unwrap();
+        let options: Vec<(&str, &str)> = options
+            .iter()
+            .map(|(i, j)| (i.as_str(), j.as_str()))
+            .collect();
+        assert_eq!(
+            options,
+            [
+                ("--terminal", "true"),
+                ("-n", "Name"),
+                ("--categories", "Game")
+            ]
+        );
+
+        assert!(layered_options(&config, Some("office")).is_err());
+        assert!(layered_options(&"unknown = 1".parse().unwrap(), None).is_err());
+    }
+
+    #[test]
+    fn keyed_defaults() {
+        let config: Table = r#"
+            set = ["X-Company-Team=infra", "Name[de]=Name"]
+            n = "Name"
+        "#
+        .parse()
+        .unwrap();
+
+        let defaults = layered_options(&config, None).unwrap();
+        let options = [
+            ("--set".to_string(), "X-Other=1".to_string()),
+            ("--set".to_string(), "Name[de] = Neu".to_string()),
+            ("--name".to_string(), "Other".to_string()),
+        ];
+        let defaults = not_overridden(defaults, &options);
+        let defaults: Vec<(&str, &str)> = defaults
+            .iter()
+            .map(|(i, j)| (i.as_str(), j.as_str()))
+            .collect();
+        assert_eq!(defaults, [("--set", "X-Company-Team=infra")]);
+    }
+}
@@ -0,0 +1,162 @@
+//! # custom_keys
+//! Parses and validates keys that are set with `--set`.
+//!
+//! Keys are written as `[Group]Key[locale]=Value`. The group is optional and defaults to `Desktop Entry`.
+//!
+//! See: https://specifications.freedesktop.org/desktop-entry-spec/latest/extending.html
+
+/// Group of the keys written by the other options.
+pub const MAIN_GROUP: &str = "Desktop Entry";
+/// Prefix of the additional application action groups.
+const ACTION_GROUP_PREFIX: &str = "Desktop Action ";
+
+/// Keys of the main group that can be localized. Their unlocalized values are set with the options.
+const LOCALIZED_KEYS: [&str; 5] = ["Name", "GenericName", "Comment", "Icon", "Keywords"];
+/// Keys of the action groups.
+const ACTION_KEYS: [&str; 3] = ["Name", "Icon", "Exec"];
+
+/// Key written in addition to the keys of the options.
+pub struct CustomKey {
+    pub group: String,
+    /// Key name including the locale, e.g. `Name[de]`.
+    pub key: String,
+    pub value: String,
+}
+
+impl CustomKey {
+    /// Parses `[Group]Key[locale]=Value`.
+    /// Returns error if the key is a standard key that can not be set or if the custom key does not start with `X-`.
+    pub fn parse(text: &str) -> Result<CustomKey, String> {
+        let (key, value) = text
+            .split_once('=')
+            .ok_or(format!("{text} must be in the Key=Value format"))?;
+        if value.contains(['\n', '\r']) {
+            return Err(format!("value of the {key} contains a line break"));
+        }
+
+        let (group, key) = match key.strip_prefix('[') {
+            Some(val) => val
+                .split_once(']')
+                .ok_or(format!("{text} has an unclosed group name"))?,
+            None => (MAIN_GROUP, key),
+        };
+        let key = key.trim();
+        if group.is_empty() || group.chars().any(|i| i.is_control() || i == '[') {
+            return Err(format!("invalid group name {group}"));
+        }
+
+        let (name, locale) = match key.strip_suffix(']').and_then(|i| i.split_once('[')) {
+            Some((name, locale)) => (name, Some(locale)),
+            None => (key, None),
+        };
+        if name.is_empty() || !name.chars().all(|i| i.is_ascii_alphanumeric() || i == '-') {
+            return Err(format!("invalid key name {key}"));
+        }
+        if locale.is_some_and(|i| i.is_empty() || i.contains(['[', ']'])) {
+            return Err(format!("invalid locale of the {key}"));
+        }
+
+        validate_key(group, name, locale.is_some())?;
+
+        let custom_key = CustomKey {
+            group: group.to_string(),
+            key: key.to_string(),
+            value: value.trim().to_string(),
+        };
+        // Action id is written into the Actions list
+        if custom_key.action().is_some_and(|i| {
+            i.is_empty() || !i.chars().all(|j| j.is_ascii_alphanumeric() || j == '-')
+        }) {
+            return Err(format!("invalid action id in the group {group}"));
+        }
+
+        Ok(custom_key)
+    }
+
+    /// Returns id of the action if the key belongs to an action group.
+    pub fn action(&self) -> Option<&str> {
+        self.group.strip_prefix(ACTION_GROUP_PREFIX)
+    }
+}
+
+/// Validates that `name` can be set in the `group`.
+fn validate_key(group: &str, name: &str, localized: bool) -> Result<(), String> {
+    let custom_group = group != MAIN_GROUP && !group.starts_with(ACTION_GROUP_PREFIX);
+    if custom_group && !group.starts_with("X-") {
+        return Err(format!("custom group {group} must start with X-"));
+    }
+    if custom_group || name.starts_with("X-") {
+        return Ok(());
+    }
+
+    if group == MAIN_GROUP {
+        return match localized && LOCALIZED_KEYS.contains(&name) {
+            true => Ok(()),
+            false if localized => Err(format!("{name} can not be localized")),
+            false => Err(format!(
+                "{name} can not be set with --set, custom keys must start with X-"
+            )),
+        };
+    }
+
+    match ACTION_KEYS.contains(&name) {
+        true => Ok(()),
+        false => Err(format!(
+            "{name} is not an action key, custom keys must start with X-"
+        )),
+    }
+}
+
+/// Returns error if an action group does not have the `Name` key.
+pub fn validate_actions(keys: &[CustomKey]) -> Result<(), String> {
+    for i in keys.iter().filter(|i| i.action().is_some()) {
+        if !keys.iter().any(|j| j.group == i.group && j.key == "Name") {
+            return Err(format!("group {} must have the Name key", i.group));
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns keys of the `group` in the key file format.
+pub fn group_text(keys: &[CustomKey], group: &str) -> String {
+    keys.iter()
+        .filter(|i| i.group == group)
+        .map(|i| format!("{}={}\n", i.key, i.value))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_key_parsing() {
+        let key = CustomKey::parse("X-GNOME-UsesNotifications=true").unwrap();
+        assert_eq!(key.group, MAIN_GROUP);
+        assert_eq!(key.key, "X-GNOME-UsesNotifications");
+        assert_eq!(key.value, "true");
+
+        let key = CustomKey::parse("[Desktop Action new]Name[de]=Neues Fenster").unwrap();
+        assert_eq!(key.group, "Desktop Action new");
+        assert_eq!(key.key, "Name[de]");
+
+        assert!(CustomKey::parse("Name[de]=Name").is_ok());
+        assert!(CustomKey::parse("[X-Company Settings]Mode=fast").is_ok());
+        assert!(CustomKey::parse("Name=Name").is_err());
+        assert!(CustomKey::parse("Company-Key=1").is_err());
+        assert!(CustomKey::parse("Terminal[de]=true").is_err());
+        assert!(CustomKey::parse("[Company]X-Key=1").is_err());
+        assert!(CustomKey::parse("[Company]Key=1").is_err());
+        assert!(CustomKey::parse("X-Key").is_err());
+        assert!(CustomKey::parse("[Desktop Action a;b]Name=A").is_err());
+
+        let keys = [
+            CustomKey::parse("[Desktop Action new]Name=New").unwrap(),
+            CustomKey::parse("[Desktop Action new]Exec=app --new").unwrap(),
+        ];
+        assert_eq!(keys[1].action(), Some("new"));
+        assert!(validate_actions(&keys).is_ok());
+        assert!(validate_actions(&keys[1..]).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+//! # desktops
+//! Validates the `OnlyShownIn` and `NotShownIn` keys against the registered desktop environments.
+//!
+//! See: https://specifications.freedesktop.org/menu-spec/latest/onlyshowin-registry.html
+
+use std::env;
+
+use crate::categories::edit_distance;
+
+/// Registered `XDG_CURRENT_DESKTOP` names.
+const DESKTOP_ENVIRONMENTS: [&str; 20] = [
+    "GNOME",
+    "GNOME-Classic",
+    "GNOME-Flashback",
+    "KDE",
+    "LXDE",
+    "LXQt",
+    "MATE",
+    "Razor",
+    "ROX",
+    "TDE",
+    "Unity",
+    "XFCE",
+    "EDE",
+    "Cinnamon",
+    "Pantheon",
+    "Budgie",
+    "Enlightenment",
+    "DDE",
+    "Endless",
+    "Old",
+];
+
+/// Maximum edit distance of the suggested desktop environment.
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+/// Validates desktop environments of the `OnlyShownIn` and `NotShownIn` keys. Names with the `X-` prefix are not
+/// validated. Returns error for the unknown names and names used in both keys.
+pub fn validate_desktops(only_shown_in: &[String], not_shown_in: &[String]) -> Result<(), String> {
+    for desktop in only_shown_in.iter().chain(not_shown_in) {
+        if desktop.starts_with("X-") || DESKTOP_ENVIRONMENTS.contains(&desktop.as_str()) {
+            continue;
+        }
+
+        return Err(match suggest_desktop(desktop) {
+            Some(val) => format!("unknown desktop environment {desktop}, did you mean {val}?"),
+            None => format!(
+                "unknown desktop environment {desktop}, custom desktop environments must start with X-"
+            ),
+        });
+    }
+
+    if let Some(desktop) = only_shown_in.iter().find(|i| not_shown_in.contains(i)) {
+        return Err(format!(
+            "desktop environment {desktop} is used in both OnlyShownIn and NotShownIn"
+        ));
+    }
+
+    Ok(())
+}
+
+/// Returns registered desktop environments listed in `$XDG_CURRENT_DESKTOP`.
+pub fn current_desktops() -> Result<Vec<String>, String> {
+    let current = env::var("XDG_CURRENT_DESKTOP")
+        .ok()
+        .filter(|i| !i.is_empty())
+        .ok_or(String::from("XDG_CURRENT_DESKTOP is not set"))?;
+
+    parse_current_desktops(&current)
+}
+
+/// Returns registered desktop environments of the colon-separated `current` list.
+fn parse_current_desktops(current: &str) -> Result<Vec<String>, String> {
+    // Distributions prepend their own names, e.g. "ubuntu:GNOME"
+    let desktops: Vec<String> = current
+        .split(':')
+        .filter(|i| i.starts_with("X-") || DESKTOP_ENVIRONMENTS.contains(i))
+        .map(String::from)
+        .collect();
+    if desktops.is_empty() {
+        return Err(format!(
+            "XDG_CURRENT_DESKTOP ({current}) does not contain registered desktop environments"
+        ));
+    }
+
+    Ok(desktops)
+}
+
+/// Returns registered desktop environment that is the closest to the misspelled `desktop`.
+fn suggest_desktop(desktop: &str) -> Option<&'static str> {
+    let desktop = desktop.to_lowercase();
+
+    DESKTOP_ENVIRONMENTS
+        .into_iter()
+        .map(|name| (edit_distance(&desktop, &name.to_lowercase()), name))
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(desktops: &[&str]) -> Vec<String> {
+        desktops.iter().map(|i| i.to_string()).collect()
+    }
+
+    #[test]
+    fn desktop_validation() {
+        assert!(validate_desktops(&list(&["GNOME", "X-Custom"]), &list(&["KDE"])).is_ok());
+        assert!(validate_desktops(&list(&["Gnome"]), &[]).is_err());
+        assert!(validate_desktops(&list(&["XFCE"]), &list(&["XFCE"])).is_err());
+
+        assert_eq!(suggest_desktop("Gnome"), Some("GNOME"));
+        assert_eq!(suggest_desktop("xfce4"), Some("XFCE"));
+        assert_eq!(suggest_desktop("Nothing"), None);
+
+        assert_eq!(parse_current_desktops("ubuntu:GNOME").unwrap(), ["GNOME"]);
+        assert_eq!(
+            parse_current_desktops("KDE:X-Custom").unwrap(),
+            ["KDE", "X-Custom"]
+        );
+        assert!(parse_current_desktops("pop").is_err());
+    }
+}
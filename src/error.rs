@@ -0,0 +1,26 @@
+//! # error
+//! Contains the error of the entry creation. Each error has its own program exit code.
+
+use std::process::exit;
+
+/// Error that closes the program with the `code` when the entry is created from the command line.
+#[derive(Debug)]
+pub struct Error {
+    pub code: i32,
+    pub message: String,
+}
+
+impl Error {
+    pub fn new(code: i32, message: impl Into<String>) -> Error {
+        Error {
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// Prints error message and closes program with the error code.
+    pub fn exit(&self) -> ! {
+        println!("mkdsk: {}", self.message);
+        exit(self.code)
+    }
+}
@@ -4,183 +4,261 @@
 //! # Add new argument
 //! To add new argument do the following:
 //! 1. Create argument handler function.
-//! 2. Create new `ArgOption` entry in `OPTION_LIST`. Pass **arg name** and **arg handler** to your `ArgOption` structure.
+//! 2. Create new `ArgOption` entry in `OPTION_LIST`. Pass **arg name**, optional short **alias** and **arg handler** to
+//!    your `ArgOption` structure.
 //! 3. Run `cargo test` to ensure that there are no options with the same argument name.
 
-use std::{path::PathBuf, process::exit, str::FromStr};
+use std::{path::PathBuf, str::FromStr};
 
 use path_absolutize::Absolutize;
 
+use crate::custom_keys::CustomKey;
+use crate::error::Error;
+use crate::icon::lookup_icon;
+use crate::keyfile::split_list;
+use crate::settings::DEFAULT_ICON_SIZE;
+use crate::shortcut::{Format, SpecVersion};
 use crate::Shortcut;
 
-/// Structure that contain **argument name** and **argument handler** function pointer.
+/// Structure that contain **argument name**, its optional short **alias** and **argument handler** function pointer.
 #[derive(Debug)]
 struct ArgOption<'a> {
     option: &'a str,
-    handler: fn(&str, &mut Shortcut),
+    alias: Option<&'a str>,
+    handler: fn(&str, &mut Shortcut) -> Result<(), Error>,
+}
+
+impl ArgOption<'_> {
+    /// Returns `true` if `name` is the argument name or its alias.
+    fn matches(&self, name: &str) -> bool {
+        self.option == name || self.alias == Some(name)
+    }
 }
 
 /// Cointans all arguments data.
-const OPTION_LIST: [ArgOption; 33] = [
-    ArgOption {
-        option: "-o",
-        handler: out_handler,
-    },
+const OPTION_LIST: [ArgOption; 37] = [
     ArgOption {
         option: "--out",
+        alias: Some("-o"),
         handler: out_handler,
     },
-    ArgOption {
-        option: "-n",
-        handler: name_handler,
-    },
     ArgOption {
         option: "--name",
+        alias: Some("-n"),
         handler: name_handler,
     },
-    ArgOption {
-        option: "-c",
-        handler: comment_handler,
-    },
     ArgOption {
         option: "--comment",
+        alias: Some("-c"),
         handler: comment_handler,
     },
     ArgOption {
-        option: "-i",
+        option: "--icon",
+        alias: Some("-i"),
         handler: icon_handler,
     },
     ArgOption {
-        option: "--icon",
-        handler: icon_handler,
+        option: "--install-icon",
+        alias: None,
+        handler: install_icon_handler,
     },
     ArgOption {
-        option: "-a",
-        handler: args_handler,
+        option: "--no-auto-icon",
+        alias: None,
+        handler: no_auto_icon_handler,
     },
     ArgOption {
         option: "--args",
+        alias: Some("-a"),
         handler: args_handler,
     },
-    ArgOption {
-        option: "-g",
-        handler: gname_handler,
-    },
     ArgOption {
         option: "--gname",
+        alias: Some("-g"),
         handler: gname_handler,
     },
     ArgOption {
         option: "--no-display",
+        alias: None,
         handler: no_display_handler,
     },
     ArgOption {
         option: "--hidden",
+        alias: None,
         handler: hidden_handler,
     },
     ArgOption {
         option: "--only-shown-in",
+        alias: None,
         handler: only_shown_handler,
     },
     ArgOption {
         option: "--not-shown-in",
+        alias: None,
         handler: not_shown_handler,
     },
     ArgOption {
         option: "--d-bus",
+        alias: None,
         handler: d_bus_handler,
     },
     ArgOption {
         option: "--try-exec",
+        alias: None,
         handler: try_exec_handler,
     },
-    ArgOption {
-        option: "-p",
-        handler: path_handler,
-    },
     ArgOption {
         option: "--path",
+        alias: Some("-p"),
         handler: path_handler,
     },
     ArgOption {
-        option: "-t",
+        option: "--terminal",
+        alias: Some("-t"),
         handler: terminal_handler,
     },
     ArgOption {
-        option: "--terminal",
-        handler: terminal_handler,
+        option: "--submenu",
+        alias: None,
+        handler: submenu_handler,
+    },
+    ArgOption {
+        option: "--current-desktop-only",
+        alias: None,
+        handler: current_desktop_only_handler,
+    },
+    ArgOption {
+        option: "--file-manager",
+        alias: None,
+        handler: file_manager_handler,
+    },
+    ArgOption {
+        option: "--webapp",
+        alias: None,
+        handler: webapp_handler,
+    },
+    ArgOption {
+        option: "--format",
+        alias: None,
+        handler: format_handler,
+    },
+    ArgOption {
+        option: "--spec-version",
+        alias: None,
+        handler: spec_version_handler,
+    },
+    ArgOption {
+        option: "--set",
+        alias: None,
+        handler: set_handler,
+    },
+    ArgOption {
+        option: "--explicit-defaults",
+        alias: None,
+        handler: explicit_defaults_handler,
+    },
+    ArgOption {
+        option: "--java",
+        alias: None,
+        handler: java_handler,
+    },
+    ArgOption {
+        option: "--wine",
+        alias: None,
+        handler: wine_handler,
+    },
+    ArgOption {
+        option: "--wine-prefix",
+        alias: None,
+        handler: wine_prefix_handler,
+    },
+    ArgOption {
+        option: "--chmod",
+        alias: None,
+        handler: chmod_handler,
     },
     ArgOption {
         option: "--actions",
+        alias: None,
         handler: actions_handler,
     },
-    // ArgOption {
-    //     option: "-u",
-    //     handler: url_handler,
-    // },
-    // ArgOption {
-    //     option: "--url",
-    //     handler: url_handler,
-    // },
     ArgOption {
         option: "--non-default-gpu",
+        alias: None,
         handler: non_default_gpu_handler,
     },
-    ArgOption {
-        option: "-s",
-        handler: single_main_window_handler,
-    },
     ArgOption {
         option: "--single-window",
+        alias: Some("-s"),
         handler: single_main_window_handler,
     },
-    ArgOption {
-        option: "-m",
-        handler: mime_type_handler,
-    },
     ArgOption {
         option: "--mime-type",
+        alias: Some("-m"),
         handler: mime_type_handler,
     },
     ArgOption {
         option: "--categories",
+        alias: None,
         handler: categories_handler,
     },
     ArgOption {
         option: "--implements",
+        alias: None,
         handler: implements_handler,
     },
     ArgOption {
         option: "--keywords",
+        alias: None,
         handler: keywords_handler,
     },
     ArgOption {
         option: "--startup-notify",
+        alias: None,
         handler: startup_notify_handler,
     },
     ArgOption {
         option: "--startup-wm-class",
+        alias: None,
         handler: startup_wm_class_handler,
     },
 ];
 
 /// Function that call argument handler for all arguments, except arguments at `PATH_TO_EXEC_INDEX` position.
-pub fn validate_option(option: String, option_val: String, shortcut: &mut Shortcut) {
-    for i in OPTION_LIST {
-        if i.option == option {
-            (i.handler)(&option_val, shortcut);
-            return;
-        }
+pub fn validate_option(
+    option: &str,
+    option_val: &str,
+    shortcut: &mut Shortcut,
+) -> Result<(), Error> {
+    match OPTION_LIST.iter().find(|i| i.matches(option)) {
+        Some(val) => (val.handler)(option_val, shortcut),
+        None => Err(Error::new(2, format!("invalid option {option}"))),
     }
+}
+
+/// Returns `true` if `option` is a valid argument name.
+pub fn is_option(option: &str) -> bool {
+    OPTION_LIST.iter().any(|i| i.matches(option))
+}
+
+/// Returns `true` if both argument names belong to the same option, e.g. `-n` and `--name`.
+pub fn is_same_option(a: &str, b: &str) -> bool {
+    a == b || OPTION_LIST.iter().any(|i| i.matches(a) && i.matches(b))
+}
 
-    println!("mkdsk: invalid option {option}");
-    exit(2);
+/// Returns the key of a keyed option value, e.g. `X-Key` of `--set X-Key=value`. Keyed values override only the values
+/// with the same key.
+pub fn option_key<'a>(option: &str, value: &'a str) -> Option<&'a str> {
+    match is_same_option(option, "--set") {
+        true => value.split_once('=').map(|(key, _)| key.trim()),
+        false => None,
+    }
 }
 
 //
 //
 //
-fn out_handler(value: &str, shortcut: &mut Shortcut) {
+fn out_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
     let mut out = PathBuf::from(value);
 
     if out.is_dir() {
@@ -188,156 +266,320 @@ fn out_handler(value: &str, shortcut: &mut Shortcut) {
         out = out.absolutize().unwrap().to_path_buf();
 
         shortcut.set_out(out);
-        return;
+        return Ok(());
     }
 
-    println!("mkdsk: invalid output directory {}", out.to_str().unwrap());
-    exit(3);
+    Err(Error::new(
+        3,
+        format!("invalid output directory {}", out.to_str().unwrap()),
+    ))
 }
-fn name_handler(value: &str, shortcut: &mut Shortcut) {
+fn name_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
     shortcut.set_name(value.to_string());
+
+    Ok(())
 }
-fn comment_handler(value: &str, shortcut: &mut Shortcut) {
+fn comment_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
     shortcut.set_comment(value.to_string());
+
+    Ok(())
 }
-fn icon_handler(value: &str, shortcut: &mut Shortcut) {
+fn icon_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
+    // Icon is either an absolute path or a name that is looked up in the icon theme
+    if value.contains('/') {
+        if !PathBuf::from(value).is_file() {
+            println!("mkdsk: warning: icon file does not exist {value}");
+        }
+    } else if lookup_icon(value, DEFAULT_ICON_SIZE, 1).is_none() {
+        println!("mkdsk: warning: icon {value} can not be found in the icon theme");
+    }
+
     shortcut.set_icon(value.to_string());
+
+    Ok(())
+}
+fn install_icon_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
+    let icon = PathBuf::from(value);
+
+    if !icon.is_file() {
+        return Err(Error::new(17, format!("icon file does not exist {value}")));
+    }
+
+    shortcut.set_install_icon(icon.absolutize().unwrap().to_path_buf());
+
+    Ok(())
+}
+fn no_auto_icon_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
+    let no_auto_icon = bool_option(value, 21)?;
+
+    shortcut.set_auto_icon(!no_auto_icon);
+
+    Ok(())
 }
-fn args_handler(value: &str, shortcut: &mut Shortcut) {
+fn args_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
     shortcut.set_args(value.to_string());
+
+    Ok(())
 }
-fn gname_handler(value: &str, shortcut: &mut Shortcut) {
+fn gname_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
     shortcut.set_generic_name(value.to_string());
+
+    Ok(())
 }
-fn no_display_handler(value: &str, shortcut: &mut Shortcut) {
-    let no_display = match value.to_lowercase().as_str() {
-        "true" | "1" => true,
-        "false" | "0" => false,
-        _ => {
-            println!("mkdsk: invalid option value");
-            exit(7)
-        }
-    };
+fn no_display_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
+    let no_display = bool_option(value, 7)?;
 
     shortcut.set_no_display(no_display);
+
+    Ok(())
 }
-fn hidden_handler(value: &str, shortcut: &mut Shortcut) {
-    let hidden = match value.to_lowercase().as_str() {
-        "true" | "1" => true,
-        "false" | "0" => false,
-        _ => {
-            println!("mkdsk: invalid option value");
-            exit(8)
-        }
-    };
+fn hidden_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
+    let hidden = bool_option(value, 8)?;
 
     shortcut.set_hidden(hidden);
+
+    Ok(())
 }
-fn only_shown_handler(value: &str, shortcut: &mut Shortcut) {
-    shortcut.set_only_shown_in(value.to_string());
+fn only_shown_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
+    shortcut.add_only_shown_in(list_items(value));
+
+    Ok(())
 }
-fn not_shown_handler(value: &str, shortcut: &mut Shortcut) {
-    shortcut.set_not_shown_in(value.to_string());
+fn not_shown_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
+    shortcut.add_not_shown_in(list_items(value));
+
+    Ok(())
 }
-fn d_bus_handler(value: &str, shortcut: &mut Shortcut) {
-    let d_bus = match value.to_lowercase().as_str() {
-        "true" | "1" => true,
-        "false" | "0" => false,
-        _ => {
-            println!("mkdsk: invalid option value");
-            exit(9)
-        }
-    };
+fn d_bus_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
+    let d_bus = bool_option(value, 9)?;
 
     shortcut.set_d_bus_activatable(d_bus);
+
+    Ok(())
 }
-fn try_exec_handler(value: &str, shortcut: &mut Shortcut) {
+fn try_exec_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
     shortcut.set_try_exec(value.to_string());
+
+    Ok(())
 }
-fn path_handler(value: &str, shortcut: &mut Shortcut) {
+fn path_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
     let path = match PathBuf::from_str(value) {
         Ok(val) => val,
-        Err(_) => {
-            println!("mkdsk: invalid path");
-            exit(10)
-        }
+        Err(_) => return Err(Error::new(10, "invalid path")),
     };
 
     if let false = path.is_dir() {
-        println!("mkdsk: path is not exists on disk or is not a directory");
-        exit(10)
+        return Err(Error::new(
+            10,
+            "path is not exists on disk or is not a directory",
+        ));
     }
 
     shortcut.set_path(value.to_string());
+
+    Ok(())
 }
-fn terminal_handler(value: &str, shortcut: &mut Shortcut) {
-    let use_terminal = match value.to_lowercase().as_str() {
-        "true" | "1" => true,
-        "false" | "0" => false,
-        _ => {
-            println!("mkdsk: invalid option value");
-            exit(11)
+fn terminal_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
+    let use_terminal = bool_option(value, 11)?;
+
+    shortcut.set_terminal(use_terminal);
+
+    Ok(())
+}
+fn submenu_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
+    if value.trim().is_empty() {
+        return Err(Error::new(39, "invalid submenu name"));
+    }
+
+    shortcut.set_submenu(value.trim().to_string());
+
+    Ok(())
+}
+fn current_desktop_only_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
+    let current_desktop_only = bool_option(value, 42)?;
+
+    shortcut.set_current_desktop_only(current_desktop_only);
+
+    Ok(())
+}
+fn file_manager_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
+    let file_manager = bool_option(value, 37)?;
+
+    shortcut.set_file_manager(file_manager);
+
+    Ok(())
+}
+fn webapp_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
+    let webapp = bool_option(value, 35)?;
+
+    shortcut.set_webapp(webapp);
+
+    Ok(())
+}
+fn format_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
+    let format = match Format::from_str(value) {
+        Ok(val) => val,
+        Err(_) => return Err(Error::new(33, format!("invalid format {value}"))),
+    };
+
+    shortcut.set_format(format);
+
+    Ok(())
+}
+fn spec_version_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
+    let spec_version = match SpecVersion::from_str(value) {
+        Ok(val) => val,
+        Err(_) => {
+            return Err(Error::new(
+                44,
+                format!("invalid specification version {value}"),
+            ))
         }
     };
 
-    shortcut.set_terminal(use_terminal);
+    shortcut.set_spec_version(spec_version);
+
+    Ok(())
+}
+fn set_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
+    let custom_key = CustomKey::parse(value).map_err(|e| Error::new(45, e))?;
+
+    shortcut.add_custom_key(custom_key);
+
+    Ok(())
+}
+fn explicit_defaults_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
+    let explicit_defaults = bool_option(value, 46)?;
+
+    shortcut.set_explicit_defaults(explicit_defaults);
+
+    Ok(())
+}
+fn java_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
+    let java = PathBuf::from(value);
+
+    if java.is_file() {
+        shortcut.set_java(java.absolutize().unwrap().to_path_buf());
+    } else if which::which(value).is_ok() {
+        shortcut.set_java(java);
+    } else {
+        return Err(Error::new(
+            27,
+            format!("java executable does not exist {value}"),
+        ));
+    }
+
+    Ok(())
 }
-fn actions_handler(value: &str, shortcut: &mut Shortcut) {
-    shortcut.set_actions(value.to_string());
+fn wine_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
+    let wine = PathBuf::from(value);
+
+    if wine.is_file() {
+        shortcut.set_wine(wine.absolutize().unwrap().to_path_buf());
+    } else if which::which(value).is_ok() {
+        shortcut.set_wine(wine);
+    } else {
+        return Err(Error::new(
+            29,
+            format!("wine executable does not exist {value}"),
+        ));
+    }
+
+    Ok(())
+}
+fn wine_prefix_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
+    let prefix = PathBuf::from(value);
+
+    if !prefix.is_dir() {
+        return Err(Error::new(
+            30,
+            format!("wine prefix does not exist {value}"),
+        ));
+    }
+
+    shortcut.set_wine_prefix(prefix.absolutize().unwrap().to_path_buf());
+
+    Ok(())
+}
+fn chmod_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
+    let chmod = bool_option(value, 24)?;
+
+    shortcut.set_chmod(chmod);
+
+    Ok(())
+}
+fn actions_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
+    shortcut.add_actions(list_items(value));
+
+    Ok(())
 }
 // fn url_handler(value: &str, shortcut: &mut Shortcut) {
 //     shortcut.set_url(value.to_string());
 // }
-fn non_default_gpu_handler(value: &str, shortcut: &mut Shortcut) {
-    let non_default_gpu = match value.to_lowercase().as_str() {
-        "true" | "1" => true,
-        "false" | "0" => false,
-        _ => {
-            println!("mkdsk: invalid option value");
-            exit(13)
-        }
-    };
+fn non_default_gpu_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
+    let non_default_gpu = bool_option(value, 13)?;
 
     shortcut.set_prefers_non_default_gpu(non_default_gpu);
+
+    Ok(())
 }
-fn single_main_window_handler(value: &str, shortcut: &mut Shortcut) {
-    let single_main_window = match value.to_lowercase().as_str() {
-        "true" | "1" => true,
-        "false" | "0" => false,
-        _ => {
-            println!("mkdsk: invalid option value");
-            exit(14)
-        }
-    };
+fn single_main_window_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
+    let single_main_window = bool_option(value, 14)?;
 
     shortcut.set_single_main_window(single_main_window);
+
+    Ok(())
 }
-fn mime_type_handler(value: &str, shortcut: &mut Shortcut) {
-    shortcut.set_mime_type(value.to_string());
+fn mime_type_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
+    shortcut.add_mime_type(list_items(value));
+
+    Ok(())
 }
-fn categories_handler(value: &str, shortcut: &mut Shortcut) {
-    shortcut.set_categories(value.to_string());
+fn categories_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
+    shortcut.add_categories(list_items(value));
+
+    Ok(())
 }
-fn implements_handler(value: &str, shortcut: &mut Shortcut) {
-    shortcut.set_implements(value.to_string());
+fn implements_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
+    shortcut.add_implements(list_items(value));
+
+    Ok(())
 }
-fn keywords_handler(value: &str, shortcut: &mut Shortcut) {
-    shortcut.set_keywords(value.to_string());
+fn keywords_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
+    shortcut.add_keywords(list_items(value));
+
+    Ok(())
 }
-fn startup_notify_handler(value: &str, shortcut: &mut Shortcut) {
-    let startup_notify = match value.to_lowercase().as_str() {
-        "true" | "1" => true,
-        "false" | "0" => false,
-        _ => {
-            println!("mkdsk: invalid option value");
-            exit(15)
-        }
-    };
+fn startup_notify_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
+    let startup_notify = bool_option(value, 15)?;
 
     shortcut.set_startup_notify(startup_notify);
+
+    Ok(())
 }
-fn startup_wm_class_handler(value: &str, shortcut: &mut Shortcut) {
+fn startup_wm_class_handler(value: &str, shortcut: &mut Shortcut) -> Result<(), Error> {
     shortcut.set_startup_wm_class(value.to_string());
+
+    Ok(())
+}
+
+/// Parses value of the boolean option. Returns error with the `code` if the value is invalid.
+fn bool_option(value: &str, code: i32) -> Result<bool, Error> {
+    match value.to_lowercase().as_str() {
+        "true" | "1" => Ok(true),
+        "false" | "0" => Ok(false),
+        _ => Err(Error::new(code, "invalid option value")),
+    }
+}
+
+/// Splits value of the list option into items. Items are separated with commas or semicolons, `\\,` and `\\;` are
+/// kept in the items.
+fn list_items(value: &str) -> Vec<String> {
+    split_list(value, &[',', ';'])
+        .into_iter()
+        .map(|i| i.trim().to_string())
+        .filter(|i| !i.is_empty())
+        .collect()
 }
 
 #[cfg(test)]
@@ -346,17 +588,39 @@ mod tests {
 
     #[test]
     fn arg_name_duplicate() {
-        for i in OPTION_LIST {
-            for j in OPTION_LIST {
-                assert!(
-                    !(i.handler != j.handler && i.option == j.option),
-                    "{}",
-                    format!(
-                        "OPTION_LIST contains options with the same argument name: {:?}, {:?}",
-                        i, j
-                    )
-                );
-            }
+        let names: Vec<&str> = OPTION_LIST
+            .iter()
+            .flat_map(|i| [Some(i.option), i.alias])
+            .flatten()
+            .collect();
+
+        for (pos, i) in names.iter().enumerate() {
+            assert!(
+                !names[pos + 1..].contains(i),
+                "OPTION_LIST contains options with the same argument name: {i}"
+            );
         }
+
+        assert!(is_same_option("-n", "--name"));
+        assert!(!is_same_option("-n", "--comment"));
+        assert_eq!(option_key("--set", "Name[de] = Name"), Some("Name[de]"));
+        assert_eq!(option_key("-n", "Name=Name"), None);
+    }
+
+    #[test]
+    fn list_option_values() {
+        assert_eq!(list_items("Graphics;Viewer;"), ["Graphics", "Viewer"]);
+        assert_eq!(
+            list_items("image/png, image/jpeg"),
+            ["image/png", "image/jpeg"]
+        );
+        assert_eq!(list_items("a\\;b,c\\,d;;"), ["a;b", "c,d"]);
+
+        // The first option replaces categories of the source, the next ones are appended
+        let mut shortcut = Shortcut::new();
+        shortcut.prefill_categories(vec![String::from("Network")]);
+        validate_option("--categories", "Utility", &mut shortcut).unwrap();
+        validate_option("--categories", "System", &mut shortcut).unwrap();
+        assert_eq!(shortcut.get_categories(), ["Utility", "System"]);
     }
 }
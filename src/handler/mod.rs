@@ -2,29 +2,77 @@
 //! This module contains functions that handles all arguments provided by the user.
 
 mod arg_options;
+mod subcommands;
 
+use crate::categories::{filter_categories, validate_categories};
+use crate::custom_keys::validate_actions;
+use crate::desktops::{current_desktops, validate_desktops};
+use crate::error::Error;
+use crate::icon::{discover_icon, icon_id, lookup_favicon, ThemeIcon};
+use crate::menu::{create_submenu, submenu_category};
 use crate::settings::*;
-use crate::shortcut::{EntryType, Shortcut};
-use std::{env::current_dir, os::unix::fs::PermissionsExt, path::PathBuf, process::exit};
+use crate::shortcut::{file_url, quote_exec, EntryType, Shortcut};
+use crate::source::{
+    appimage::AppImage,
+    flatpak::Flatpak,
+    jar::{Jar, JAVA_COMMAND},
+    pe::{is_pe, Pe, WINE_COMMAND},
+    script::{script_interpreter, shebang},
+    snap::Snap,
+};
+use crate::webapp::apply_webapp;
+use std::{
+    env::current_dir,
+    fs::{self, Permissions},
+    os::unix::fs::PermissionsExt,
+    path::PathBuf,
+};
 
-pub use arg_options::validate_option;
+pub use arg_options::{is_option, is_same_option, option_key, validate_option};
 use is_url::is_url;
 use path_absolutize::Absolutize;
+pub use subcommands::run_subcommand;
 
 /// Prints help message. This function is called if `-h` or `--help` argument was provided, or if no arguments were provided.
 /// Program should be closed after calling this function.
 pub fn print_help() {
     let tab = "    ";
 
-    println!("usage: mkdsk [source] [options]...\n");
+    println!("usage: mkdsk [source] [options]...");
+    println!("       mkdsk [subcommand] [args]...\n");
 
     println!("sources: ");
     println!("{tab}{:<15}[path/to/exec]", "Application");
-    println!("{tab}{:<15}[path/to/dir]", "Directory");
+    println!("{tab}{:<15}[path/to/script]", "Script");
+    println!("{tab}{:<15}[path/to/jar]", "Java");
+    println!("{tab}{:<15}[path/to/exe]", "Windows");
+    println!("{tab}{:<15}[path/to/dir]", "Folder");
     println!("{tab}{:<15}[url]", "Link");
+    println!("{tab}{:<15}[name.directory]", "Menu directory");
+    println!("{tab}{:<15}[app id]", "Flatpak");
+    println!("{tab}{:<15}[snap name]", "Snap");
     print!("\n");
     println!("");
 
+    println!("subcommands:");
+    println!(
+        "{tab}{:<30} print path of the icon found in the icon theme",
+        "icon resolve [name] [size]"
+    );
+    println!(
+        "{tab}{:<30} watch directory (~/Applications by default) and keep AppImage launchers up to date",
+        "integrate [dir]"
+    );
+    println!(
+        "{tab}{:<30} create entries from the Windows .lnk and .url shortcuts",
+        "import [files...]"
+    );
+    println!(
+        "{tab}{:<30} create or update entries described by the .toml, .yaml or .json manifest",
+        "apply [manifest]"
+    );
+    println!();
+
     println!("options:");
     println!("{tab}{:<30} display this help and exit", "-h, --help");
     println!("{tab}{:<30} display program version", "-v, --version");
@@ -52,17 +100,29 @@ pub fn print_help() {
         "{tab}{:<20}{:<10} set application icon",
         "-i, --icon", "[str]"
     );
+    println!(
+        "{tab}{:<20}{:<10} install icon (PNG/SVG/XPM) into the hicolor icon theme and use it as application icon",
+        "--install-icon", "[str]"
+    );
+    println!(
+        "{tab}{:<20}{:<10} do not search for an icon next to the executable if icon is not set",
+        "--no-auto-icon", "[bool]"
+    );
     println!(
         "{tab}{:<20}{:<10} if this option is true - the desktop entry will be completely ignored",
         "--hidden", "[bool]"
     );
     println!(
         "{tab}{:<20}{:<10} set a list of desktop environments that should display the desktop entry",
-        "--only-shown-in", "[str]"
+        "--only-shown-in", "[list]"
     );
     println!(
         "{tab}{:<20}{:<10} set a list of desktop environments that should not display the desktop entry",
-        "--not-shown-in", "[str]"
+        "--not-shown-in", "[list]"
+    );
+    println!(
+        "{tab}{:<20}{:<10} show the entry only in the current desktop environment ($XDG_CURRENT_DESKTOP)",
+        "--current-desktop-only", "[bool]"
     );
     println!(
         "{tab}{:<20}{:<10} set if D-Bus activation is supported for the application",
@@ -84,25 +144,73 @@ pub fn print_help() {
         "{tab}{:<20}{:<10} set whether the program runs in a terminal window",
         "-t, --terminal", "[bool]"
     );
+    println!(
+        "{tab}{:<20}{:<10} show application in the menu submenu with the given name",
+        "--submenu", "[name]"
+    );
+    println!(
+        "{tab}{:<20}{:<10} create Application entry that opens folder source with the file manager",
+        "--file-manager", "[bool]"
+    );
+    println!(
+        "{tab}{:<20}{:<10} create Application entry that opens URL source in a separate browser window",
+        "--webapp", "[bool]"
+    );
+    println!(
+        "{tab}{:<20}{:<10} format of the created file: desktop (default), url, webloc or lnk",
+        "--format", "[format]"
+    );
+    println!(
+        "{tab}{:<20}{:<10} write Version key; keys newer than the version are not written: 1.0, 1.1, 1.4 or 1.5",
+        "--spec-version", "[version]"
+    );
+    println!(
+        "{tab}{:<20}{:<10} set custom key, e.g. \"X-Company-Key=value\", \"Name[de]=Name\" or \"[Desktop Action new]Name=New\"; can be repeated",
+        "--set", "[key=value]"
+    );
+    println!(
+        "{tab}{:<20}{:<10} write boolean keys that were not set with their default value (false)",
+        "--explicit-defaults", "[bool]"
+    );
+    println!(
+        "{tab}{:<20}{:<10} use options of the profile from the config file",
+        "--profile", "[name]"
+    );
+    println!(
+        "{tab}{:<20}{:<10} java used to run .jar source",
+        "--java", "[path]"
+    );
+    println!(
+        "{tab}{:<20}{:<10} wine used to run Windows executable source",
+        "--wine", "[path]"
+    );
+    println!(
+        "{tab}{:<20}{:<10} WINEPREFIX used to run Windows executable source",
+        "--wine-prefix", "[path]"
+    );
+    println!(
+        "{tab}{:<20}{:<10} make script source executable and run it directly instead of with the interpreter",
+        "--chmod", "[bool]"
+    );
     println!(
         "{tab}{:<20}{:<10} this can be used to tell the application to make a specific action",
-        "--actions", "[str]"
+        "--actions", "[list]"
     );
     println!(
         "{tab}{:<20}{:<10} set the MIME type(s) supported by the application",
-        "-m, --mime-type", "[str]"
+        "-m, --mime-type", "[list]"
     );
     println!(
         "{tab}{:<20}{:<10} set categories in which the entry should be shown in a menu",
-        "--categories", "[str]"
+        "--categories", "[list]"
     );
     println!(
         "{tab}{:<20}{:<10} set list of interfaces that this application implements",
-        "--implements", "[str]"
+        "--implements", "[list]"
     );
     println!(
         "{tab}{:<20}{:<10} set list of strings which may be used in addition to other metadata to describe this entry",
-        "--keywords", "[str]"
+        "--keywords", "[list]"
     );
     println!(
         "{tab}{:<20}{:<10} set if it is KNOWN that the application will send a \"remove\" message when started on startup",
@@ -127,41 +235,26 @@ pub fn print_help() {
 
     println!("\nNote: if you want the application to be globally accessible use \"--out ~/.local/share/applications\"");
 
+    println!("\nNote: default options are read from \"~/.config/mkdsk/config.toml\". Options of the command line take precedence over the profile, profile takes precedence over the config");
+
+    println!("\nNote: [list] options can be repeated or contain several items separated with \",\" or \";\". Use \"\\,\" and \"\\;\" to keep them in the item");
+
+    println!("\nNote: subcommands take precedence over the sources. Use \"mkdsk -- [source] [options]...\" for a source named like a subcommand");
+
     println!("\nFor more info see: https://specifications.freedesktop.org/desktop-entry-spec/latest/ar01s06.html")
 }
 
-fn print_version() {
+pub fn print_version() {
     println!("mkdsk version {PROGRAM_VERSION}");
 
     println!("\nFor more info see: {PROGRAM_GITHUB}");
 }
 
 /// Validates argument at `ENTRY_SOURCE_INDEX` position.
-/// Closes program if source is not a url/dir/executable.
-/// Prints help message and closes program if argument value is `-h` or `--help`.
-/// Prints program version and closes program if argument value is `-v` or `--version`.
+/// Returns error if source is not a url/dir/executable/Flatpak application id/snap name.
 ///
 /// This function also sets default output path and default name.
-pub fn validate_source(arg: String, shortcut: &mut Shortcut) {
-    // Handle help arg
-    if arg == "--help" || arg == "-h" {
-        print_help();
-        exit(0);
-    }
-    match arg.as_str() {
-        // Help arg
-        "--help" | "-h" => {
-            print_help();
-            exit(0);
-        }
-        // Version arg
-        "--version" | "-v" => {
-            print_version();
-            exit(0);
-        }
-        _ => (),
-    }
-
+pub fn validate_source(arg: String, shortcut: &mut Shortcut) -> Result<(), Error> {
     let exec = PathBuf::from(&arg);
 
     // Set default output dir
@@ -173,26 +266,92 @@ pub fn validate_source(arg: String, shortcut: &mut Shortcut) {
         shortcut.set_url(arg.clone());
         shortcut.set_name(DEFAULT_LINK_NAME.to_string());
 
-        return;
+        return Ok(());
     } else if exec.is_dir() {
-        shortcut.set_entry_type(EntryType::Directory);
+        // Folders are opened by URL, the path is kept for the exporters and --file-manager
+        let dir = exec.absolutize().unwrap().to_path_buf();
+        shortcut.set_entry_type(EntryType::Link);
 
-        shortcut.set_exec(exec.absolutize().unwrap().to_path_buf());
+        shortcut.set_url(file_url(&dir));
+        shortcut.set_exec(dir);
 
         shortcut.set_name(String::from(exec.file_name().unwrap().to_str().unwrap()));
 
-        return;
+        return Ok(());
     } else if exec.is_file() {
+        // Run Java archives with java
+        if exec
+            .extension()
+            .is_some_and(|i| i.eq_ignore_ascii_case("jar"))
+        {
+            shortcut.set_exec(exec.absolutize().unwrap().to_path_buf());
+
+            shortcut.set_name(String::from(exec.file_stem().unwrap().to_str().unwrap()));
+
+            let jar = Jar::read(&exec).unwrap_or_else(|e| {
+                println!("mkdsk: warning: can not read jar manifest: {e}");
+                Jar::default()
+            });
+            jar.apply(shortcut);
+        }
+        // Run Windows executables with wine
+        else if is_pe(&exec) {
+            shortcut.set_exec(exec.absolutize().unwrap().to_path_buf());
+
+            shortcut.set_name(String::from(exec.file_stem().unwrap().to_str().unwrap()));
+
+            let pe = Pe::read(&exec).unwrap_or_else(|e| {
+                println!("mkdsk: warning: can not read executable resources: {e}");
+                Pe::default()
+            });
+            pe.apply(shortcut);
+        }
         // Check if file is an executable
-        if exec.metadata().unwrap().permissions().mode() & 0o111 != 0 {
+        else if exec.metadata().unwrap().permissions().mode() & 0o111 != 0 {
+            shortcut.set_entry_type(EntryType::Application);
+
+            shortcut.set_exec(exec.absolutize().unwrap().to_path_buf());
+
+            shortcut.set_name(String::from(exec.file_name().unwrap().to_str().unwrap()));
+
+            // Prefill entry with the metadata embedded into AppImage
+            match AppImage::read(&exec) {
+                Ok(Some(appimage)) => appimage.apply(shortcut),
+                Ok(None) => (),
+                Err(e) => println!("mkdsk: warning: can not read AppImage metadata: {e}"),
+            }
+        }
+        // Run scripts that are not executable with their interpreter
+        else if let Some(interpreter) = script_interpreter(&exec) {
             shortcut.set_entry_type(EntryType::Application);
 
             shortcut.set_exec(exec.absolutize().unwrap().to_path_buf());
+            shortcut.set_interpreter(Some(interpreter));
 
             shortcut.set_name(String::from(exec.file_name().unwrap().to_str().unwrap()));
+        } else {
+            return Err(Error::new(
+                25,
+                "source file is not an executable or a script",
+            ));
         }
 
-        return;
+        return Ok(());
+    } else if let Some(flatpak) = Flatpak::find(&arg) {
+        flatpak.apply(shortcut);
+
+        return Ok(());
+    } else if let Some(snap) = Snap::find(&arg) {
+        snap.apply(shortcut);
+
+        return Ok(());
+    } else if let Some(name) = arg.strip_suffix(".directory").filter(|i| !i.is_empty()) {
+        // Menu directory that describes a submenu
+        shortcut.set_entry_type(EntryType::Directory);
+
+        shortcut.set_name(name.to_string());
+
+        return Ok(());
     } else if let Ok(_) = which::which(&arg) {
         shortcut.set_entry_type(EntryType::Application);
 
@@ -200,9 +359,212 @@ pub fn validate_source(arg: String, shortcut: &mut Shortcut) {
 
         shortcut.set_name(arg);
 
-        return;
+        return Ok(());
+    }
+
+    Err(Error::new(1, "invalid source"))
+}
+
+/// File system changes that are made together with the entry.
+pub enum Install {
+    /// Icon installed into the icon theme.
+    Icon(ThemeIcon),
+    /// Browser profile directory of the web app.
+    Profile(PathBuf),
+    /// Script that is made executable.
+    Executable(PathBuf),
+    /// Submenu the entry is shown in.
+    Submenu(String),
+}
+
+/// Performs actions that depend on all provided arguments.
+/// Must be called after all arguments were validated and before `Shortcut::create`. Files are not written, returns
+/// changes that must be made with `install` together with the entry.
+pub fn finalize(shortcut: &mut Shortcut) -> Result<Vec<Install>, Error> {
+    let mut installs = Vec::new();
+
+    // Install icon into the icon theme
+    if let Some(icon) = shortcut.get_install_icon().cloned() {
+        let icon = ThemeIcon::read(&icon, &icon_id(shortcut.desktop_id()))
+            .map_err(|e| Error::new(18, e))?;
+
+        shortcut.set_icon(icon.id().to_string());
+        installs.push(Install::Icon(icon));
+    }
+
+    // Open folder with the file manager
+    if shortcut.get_file_manager() {
+        let dir = match shortcut.get_exec() {
+            Some(val) if *shortcut.get_entry_type() == EntryType::Link => val.clone(),
+            _ => {
+                return Err(Error::new(
+                    38,
+                    "--file-manager can only be used with folder sources",
+                ))
+            }
+        };
+
+        shortcut.set_entry_type(EntryType::Application);
+        shortcut.set_exec(PathBuf::from("xdg-open"));
+        shortcut.set_args(quote_exec(dir.to_str().unwrap()));
+    }
+
+    // Open URL in a separate browser window
+    if shortcut.get_webapp() {
+        if let Some(profile) = apply_webapp(shortcut).map_err(|e| Error::new(36, e))? {
+            installs.push(Install::Profile(profile));
+        }
+    }
+
+    // Run jar with the provided java
+    if let Some(java) = shortcut.get_java() {
+        let is_jar = shortcut
+            .get_interpreter()
+            .is_some_and(|i| i == JAVA_COMMAND);
+        if !is_jar {
+            return Err(Error::new(28, "--java can only be used with .jar sources"));
+        }
+
+        let interpreter = format!("{} -jar", quote_exec(java.to_str().unwrap()));
+        shortcut.set_interpreter(Some(interpreter));
+    }
+
+    // Run Windows executable with the provided wine and prefix
+    if shortcut.get_wine().is_some() || shortcut.get_wine_prefix().is_some() {
+        let is_pe = shortcut
+            .get_interpreter()
+            .is_some_and(|i| i == WINE_COMMAND);
+        if !is_pe {
+            return Err(Error::new(
+                31,
+                "--wine and --wine-prefix can only be used with Windows executable sources",
+            ));
+        }
+
+        let mut interpreter = match shortcut.get_wine() {
+            Some(wine) => quote_exec(wine.to_str().unwrap()),
+            None => WINE_COMMAND.to_string(),
+        };
+        if let Some(prefix) = shortcut.get_wine_prefix() {
+            let prefix = quote_exec(&format!("WINEPREFIX={}", prefix.to_str().unwrap()));
+            interpreter = format!("env {prefix} {interpreter}");
+        }
+        shortcut.set_interpreter(Some(interpreter));
+    }
+
+    // Make script executable so it is run directly instead of with the interpreter
+    if shortcut.get_chmod() && shortcut.get_interpreter().is_some() {
+        let script = shortcut.get_exec().unwrap().clone();
+
+        if shebang(&script).is_none() {
+            println!("mkdsk: warning: script has no shebang, it will be run with the interpreter");
+        } else {
+            shortcut.set_interpreter(None);
+            installs.push(Install::Executable(script));
+        }
+    }
+
+    // Show application in the submenu
+    if let Some(submenu) = shortcut.get_submenu().cloned() {
+        if *shortcut.get_entry_type() != EntryType::Application {
+            return Err(Error::new(
+                39,
+                "--submenu can only be used with Application entries",
+            ));
+        }
+
+        shortcut.push_category(submenu_category(&submenu));
+        installs.push(Install::Submenu(submenu));
+    }
+
+    // Show entry only in the current desktop environment
+    if shortcut.get_current_desktop_only() {
+        let desktops = current_desktops().map_err(|e| Error::new(43, e))?;
+        shortcut.add_only_shown_in(desktops);
+    }
+
+    validate_desktops(shortcut.get_only_shown_in(), shortcut.get_not_shown_in())
+        .map_err(|e| Error::new(43, e))?;
+
+    // Validate categories including the submenu category
+    if *shortcut.get_entry_type() == EntryType::Application {
+        let only_shown_in = !shortcut.get_only_shown_in().is_empty();
+        // Categories of the source are not chosen by the user, so invalid ones are skipped
+        if shortcut.get_categories_prefilled() {
+            let categories = filter_categories(shortcut.get_categories(), only_shown_in);
+            shortcut.prefill_categories(categories);
+        }
+        validate_categories(shortcut.get_categories(), only_shown_in)
+            .map_err(|e| Error::new(41, e))?;
+    }
+
+    validate_actions(shortcut.get_custom_keys()).map_err(|e| Error::new(45, e))?;
+
+    // Keys that are newer than the declared specification version are not written
+    if let Some(version) = shortcut.get_spec_version() {
+        for (key, required) in shortcut.unsupported_keys() {
+            println!(
+                "mkdsk: warning: {key} requires specification version {}, it is not written for version {}",
+                required.get_str(),
+                version.get_str()
+            );
+        }
+    }
+
+    // Use icon embedded into the source, cached favicon of the URL or icon shipped next to the executable if icon was not set
+    if shortcut.get_auto_icon() && shortcut.get_icon().is_empty() {
+        let id = icon_id(shortcut.desktop_id());
+
+        if let Some(icon) = shortcut.get_embedded_icon() {
+            match ThemeIcon::new(icon, &id) {
+                Ok(val) => {
+                    shortcut.set_icon(val.id().to_string());
+                    installs.push(Install::Icon(val));
+                }
+                Err(e) => println!("mkdsk: warning: can not install embedded icon: {e}"),
+            }
+        } else if let Some(icon) = Some(shortcut.get_url())
+            .filter(|i| !i.is_empty())
+            .and_then(lookup_favicon)
+        {
+            match ThemeIcon::new(&icon, &id) {
+                Ok(val) => {
+                    shortcut.set_icon(val.id().to_string());
+                    installs.push(Install::Icon(val));
+                }
+                Err(e) => println!("mkdsk: warning: can not install favicon: {e}"),
+            }
+        } else if let Some(icon) = shortcut
+            .get_exec()
+            .filter(|i| i.is_absolute() && i.is_file())
+            .and_then(|i| discover_icon(i))
+        {
+            shortcut.set_icon(String::from(icon.to_str().unwrap()));
+        }
+    }
+
+    Ok(installs)
+}
+
+/// Makes file system changes returned by `finalize`.
+pub fn install(installs: &[Install]) -> Result<(), Error> {
+    for i in installs {
+        match i {
+            Install::Icon(icon) => icon.install().map_err(|e| Error::new(18, e))?,
+            Install::Profile(dir) => fs::create_dir_all(dir)
+                .map_err(|e| Error::new(36, format!("{}: {e}", dir.display())))?,
+            Install::Executable(script) => {
+                let mode = script
+                    .metadata()
+                    .map_err(|e| Error::new(26, e.to_string()))?
+                    .permissions()
+                    .mode();
+                fs::set_permissions(script, Permissions::from_mode(mode | 0o111))
+                    .map_err(|e| Error::new(26, e.to_string()))?;
+            }
+            Install::Submenu(name) => create_submenu(name).map_err(|e| Error::new(40, e))?,
+        }
     }
 
-    println!("mkdsk: invalid source");
-    exit(1);
+    Ok(())
 }
@@ -0,0 +1,163 @@
+//! # subcommands
+//! This module contains all possible subcommands. Subcommand name must be provided at `ENTRY_SOURCE_INDEX` position.
+//!
+//! # Add new subcommand
+//! To add new subcommand do the following:
+//! 1. Create subcommand handler function. Handler receives all arguments that follow the subcommand name.
+//! 2. Create new `Subcommand` entry in `SUBCOMMAND_LIST`. Pass **subcommand name** and **subcommand handler** to your `Subcommand` structure.
+
+use std::{fs, path::PathBuf, process::exit};
+
+use path_absolutize::Absolutize;
+
+use crate::apply::apply;
+use crate::icon::lookup_icon;
+use crate::import::import;
+use crate::integrate::integrate;
+use crate::settings::*;
+use crate::xdg::home_dir;
+
+/// Structure that contain **subcommand name** and **subcommand handler** function pointer.
+#[derive(Debug)]
+struct Subcommand<'a> {
+    name: &'a str,
+    handler: fn(&[String]),
+}
+
+/// Contains all subcommands data.
+const SUBCOMMAND_LIST: [Subcommand; 4] = [
+    Subcommand {
+        name: "icon",
+        handler: icon_subcommand,
+    },
+    Subcommand {
+        name: "integrate",
+        handler: integrate_subcommand,
+    },
+    Subcommand {
+        name: "import",
+        handler: import_subcommand,
+    },
+    Subcommand {
+        name: "apply",
+        handler: apply_subcommand,
+    },
+];
+
+/// Runs subcommand if the first argument is a subcommand name.
+/// Returns `false` if the first argument is not a subcommand.
+pub fn run_subcommand(args: &[String]) -> bool {
+    for i in SUBCOMMAND_LIST {
+        if args.first().is_some_and(|arg| arg == i.name) {
+            (i.handler)(&args[1..]);
+            return true;
+        }
+    }
+
+    false
+}
+
+//
+//
+//
+/// `mkdsk icon resolve [name] [size]` - prints path of the icon found in the icon theme.
+fn icon_subcommand(args: &[String]) {
+    let (name, size) = match args {
+        [action, name] if action == "resolve" => (name, DEFAULT_ICON_SIZE),
+        [action, name, size] if action == "resolve" => match size.parse::<u32>() {
+            Ok(val) => (name, val),
+            Err(_) => {
+                println!("mkdsk: invalid icon size {size}");
+                exit(20)
+            }
+        },
+        _ => {
+            println!("usage: mkdsk icon resolve [name] [size]");
+            exit(20)
+        }
+    };
+
+    match lookup_icon(name, size, 1) {
+        Some(val) => println!("{}", val.display()),
+        None => {
+            println!("mkdsk: icon {name} not found");
+            exit(19)
+        }
+    }
+}
+/// `mkdsk integrate [dir]` - creates, updates and removes launchers of the AppImages in `dir`.
+fn integrate_subcommand(args: &[String]) {
+    let dir = match args {
+        [] => home_dir().join(DEFAULT_APPIMAGE_DIR),
+        [dir] => PathBuf::from(dir),
+        _ => {
+            println!("usage: mkdsk integrate [dir]");
+            exit(20)
+        }
+    };
+
+    if !dir.is_dir() {
+        println!("mkdsk: invalid directory {}", dir.display());
+        exit(22)
+    }
+
+    if let Err(e) = integrate(&dir.absolutize().unwrap()) {
+        println!("mkdsk: {e}");
+        exit(23)
+    }
+}
+/// `mkdsk import [files...]` - creates desktop entries from the Windows `.lnk` and `.url` shortcuts.
+/// Shortcuts stored in the directories are imported too.
+fn import_subcommand(args: &[String]) {
+    if args.is_empty() {
+        println!("usage: mkdsk import [files...]");
+        exit(20)
+    }
+
+    let mut files: Vec<PathBuf> = Vec::new();
+    for i in args.iter().map(PathBuf::from) {
+        match fs::read_dir(&i) {
+            Ok(entries) => {
+                let mut entries: Vec<PathBuf> = entries
+                    .flatten()
+                    .map(|i| i.path())
+                    .filter(|i| {
+                        i.extension().is_some_and(|ext| {
+                            ext.eq_ignore_ascii_case("lnk") || ext.eq_ignore_ascii_case("url")
+                        })
+                    })
+                    .collect();
+                entries.sort();
+                files.append(&mut entries);
+            }
+            Err(_) => files.push(i),
+        }
+    }
+
+    let mut failed = false;
+    for i in files {
+        if let Err(e) = import(&i) {
+            println!("mkdsk: {}: {e}", i.display());
+            failed = true;
+        }
+    }
+
+    if failed {
+        exit(32)
+    }
+}
+/// `mkdsk apply [manifest]` - creates or updates entries described by the manifest.
+fn apply_subcommand(args: &[String]) {
+    let manifest = match args {
+        [manifest] => PathBuf::from(manifest),
+        _ => {
+            println!("usage: mkdsk apply [manifest]");
+            exit(20)
+        }
+    };
+
+    if let Err(e) = apply(&manifest) {
+        println!("mkdsk: {}: {e}", manifest.display());
+        exit(48)
+    }
+}
@@ -0,0 +1,177 @@
+//! # discover
+//! Searches for an application icon shipped next to the executable.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use super::install::{detect_icon, IconFormat};
+use crate::xdg::home_dir;
+
+/// Directories relative to the executable directory (or its parent) that are searched recursively.
+const ICON_DIRS: [&str; 5] = [
+    "share/icons",
+    "share/pixmaps",
+    "resources",
+    "icons",
+    "assets",
+];
+
+/// Maximum depth of the recursive search in `ICON_DIRS`.
+const MAX_SEARCH_DEPTH: usize = 5;
+
+/// Directories shared by many applications. Relative paths are relative to the home directory.
+const SHARED_DIRS: [&str; 15] = [
+    "/",
+    "/bin",
+    "/sbin",
+    "/usr",
+    "/usr/bin",
+    "/usr/sbin",
+    "/usr/share",
+    "/usr/local",
+    "/usr/local/bin",
+    "/usr/local/share",
+    "/opt",
+    "",
+    "bin",
+    ".local/bin",
+    ".local/share",
+];
+
+/// Looks for an icon in the executable directory and its parent. Only directories of the application itself, e.g.
+/// `/opt/<app>` or an AppDir, are searched: executables in the shared directories like `/usr/bin` are skipped since
+/// their icons are installed into the icon theme.
+///
+/// Files named `<exec name>.<ext>` or `icon.<ext>` placed directly in these directories are used first.
+/// Otherwise `ICON_DIRS` are searched for `<exec name>.<ext>` and the largest found icon is used.
+pub fn discover_icon(exec: &Path) -> Option<PathBuf> {
+    let name = exec.file_stem()?.to_str()?;
+    let dir = exec.parent().filter(|i| !is_shared_dir(i))?;
+    let dirs = [Some(dir), dir.parent().filter(|i| !is_shared_dir(i))];
+    let names = [name.to_string(), name.to_lowercase(), String::from("icon")];
+
+    for dir in dirs.iter().flatten() {
+        for name in &names {
+            for ext in ["svg", "png", "xpm"] {
+                let file = dir.join(format!("{name}.{ext}"));
+                if file.is_file() && icon_score(&file).is_some() {
+                    return Some(file);
+                }
+            }
+        }
+    }
+
+    let mut found = Vec::new();
+    for dir in dirs.iter().flatten() {
+        for icon_dir in ICON_DIRS {
+            find_icons(&dir.join(icon_dir), &names[..2], 0, &mut found);
+        }
+    }
+
+    found
+        .into_iter()
+        .filter_map(|i| icon_score(&i).map(|score| (score, i)))
+        .max_by_key(|(score, _)| *score)
+        .map(|(_, file)| file)
+}
+
+/// Returns `true` if `dir` is one of the `SHARED_DIRS`.
+fn is_shared_dir(dir: &Path) -> bool {
+    let home = home_dir();
+
+    SHARED_DIRS.iter().any(|i| match i.starts_with('/') {
+        true => dir == Path::new(i),
+        false => home.is_absolute() && dir == home.join(i),
+    })
+}
+
+/// Recursively collects files in `dir` which stem is one of the `names`.
+fn find_icons(dir: &Path, names: &[String], depth: usize, found: &mut Vec<PathBuf>) {
+    if depth > MAX_SEARCH_DEPTH {
+        return;
+    }
+    let entries = match fs::read_dir(dir) {
+        Ok(val) => val,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+
+        if path.is_dir() {
+            find_icons(&path, names, depth + 1, found);
+        } else if path
+            .file_stem()
+            .and_then(|i| i.to_str())
+            .is_some_and(|stem| names.iter().any(|i| i == stem))
+        {
+            found.push(path);
+        }
+    }
+}
+
+/// Returns how suitable the icon is. Scalable icons are preferred, raster icons are ranked by their size.
+/// Returns `None` if the file is not a supported icon.
+fn icon_score(file: &Path) -> Option<u32> {
+    let data = fs::read(file).ok()?;
+
+    match detect_icon(&data)? {
+        (IconFormat::Svg, _) => Some(u32::MAX),
+        (_, Some((width, height))) => Some(width.min(height)),
+        (_, None) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn icon_discovery() {
+        assert!(is_shared_dir(Path::new("/usr/bin")));
+        assert!(is_shared_dir(Path::new("/usr/share")));
+        assert!(!is_shared_dir(Path::new("/opt/app")));
+        assert_eq!(discover_icon(Path::new("/usr/bin/sh")), None);
+        assert_eq!(discover_icon(Path::new("/usr/share/app")), None);
+
+        let png = |size| {
+            let mut data = Vec::new();
+            image::RgbaImage::new(size, size)
+                .write_to(
+                    &mut std::io::Cursor::new(&mut data),
+                    image::ImageFormat::Png,
+                )
+                .unwrap();
+            data
+        };
+        let root = std::env::temp_dir().join(format!("mkdsk-test-{}-discover", std::process::id()));
+        let exec = root.join("bin/Tool");
+        let write = |path: &str, data: &[u8]| {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, data).unwrap();
+            path
+        };
+
+        write("bin/Tool", b"");
+        write("share/icons/hicolor/16x16/apps/tool.png", &png(16));
+        let large = write("share/icons/hicolor/64x64/apps/tool.png", &png(64));
+        write("assets/other.png", &png(128));
+        write("assets/Tool.png", b"not an icon");
+        assert_eq!(discover_icon(&exec), Some(large));
+
+        let scalable = write(
+            "resources/tool.svg",
+            b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>",
+        );
+        assert_eq!(discover_icon(&exec), Some(scalable));
+
+        // Icons placed next to the executable are used first
+        let icon = write("bin/icon.png", &png(16));
+        assert_eq!(discover_icon(&exec), Some(icon));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
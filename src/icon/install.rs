@@ -0,0 +1,253 @@
+//! # install
+//! Installs icons into the user's `hicolor` icon theme.
+
+use std::{fs, path::Path};
+
+use super::resize::{resize_icon, HICOLOR_SIZES};
+use crate::settings::ICON_PREFIX;
+use crate::shortcut::file_id;
+use crate::xdg::data_home;
+
+/// Icon image formats supported by the icon theme specification.
+#[derive(Debug, PartialEq)]
+pub enum IconFormat {
+    Png,
+    Svg,
+    Xpm,
+}
+
+impl IconFormat {
+    pub fn get_ext(&self) -> &'static str {
+        match self {
+            IconFormat::Png => "png",
+            IconFormat::Svg => "svg",
+            IconFormat::Xpm => "xpm",
+        }
+    }
+}
+
+/// Detects icon format and size from the image headers.
+/// Size is `None` for scalable images.
+pub fn detect_icon(data: &[u8]) -> Option<(IconFormat, Option<(u32, u32)>)> {
+    // PNG: signature followed by the IHDR chunk which stores width and height
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        if data.len() < 24 || &data[12..16] != b"IHDR" {
+            return None;
+        }
+        let width = u32::from_be_bytes(data[16..20].try_into().unwrap());
+        let height = u32::from_be_bytes(data[20..24].try_into().unwrap());
+
+        return Some((IconFormat::Png, Some((width, height))));
+    }
+
+    let text = String::from_utf8_lossy(data);
+
+    // XPM: the first string of the array contains "<width> <height> <colors> <chars per pixel>"
+    if text.trim_start().starts_with("/* XPM */") {
+        let values = text.split('"').nth(1)?;
+        let mut values = values.split_whitespace().map(|i| i.parse::<u32>());
+        let width = values.next()?.ok()?;
+        let height = values.next()?.ok()?;
+
+        return Some((IconFormat::Xpm, Some((width, height))));
+    }
+
+    if is_svg(&text) {
+        return Some((IconFormat::Svg, None));
+    }
+
+    None
+}
+
+/// Returns `true` if the root element of the XML document is `svg`.
+fn is_svg(text: &str) -> bool {
+    let mut text = text.trim_start_matches('\u{feff}');
+
+    // Skip XML declaration, processing instructions, comments and doctype
+    loop {
+        text = text.trim_start();
+
+        let end = if text.starts_with("<?") {
+            text.find("?>").map(|i| i + 2)
+        } else if text.starts_with("<!--") {
+            text.find("-->").map(|i| i + 3)
+        } else if text.starts_with("<!") {
+            // Doctype can contain internal subset with its own tags
+            match (text.find('['), text.find('>')) {
+                (Some(open), Some(close)) if open < close => text.find("]>").map(|i| i + 2),
+                (_, close) => close.map(|i| i + 1),
+            }
+        } else {
+            break;
+        };
+
+        match end {
+            Some(val) => text = &text[val..],
+            None => return false,
+        }
+    }
+
+    let Some(text) = text.strip_prefix('<') else {
+        return false;
+    };
+    let name = text
+        .split(|i: char| i.is_whitespace() || i == '>' || i == '/')
+        .next()
+        .unwrap_or_default();
+
+    name == "svg" || name.ends_with(":svg")
+}
+
+/// Returns icon id of the entry with the `desktop_id`. Characters that can not be used in the icon file names are
+/// replaced and the prefix keeps the icon from shadowing icons of the installed applications.
+pub fn icon_id(desktop_id: &str) -> String {
+    format!("{ICON_PREFIX}{}", file_id(desktop_id))
+}
+
+/// Icon prepared for the installation into the `hicolor` theme.
+pub struct ThemeIcon {
+    id: String,
+    /// Size directory, extension and data of the icon files.
+    files: Vec<(String, &'static str, Vec<u8>)>,
+}
+
+impl ThemeIcon {
+    /// Reads icon at `src`. See `ThemeIcon::new`.
+    pub fn read(src: &Path, id: &str) -> Result<ThemeIcon, String> {
+        let data = match fs::read(src) {
+            Ok(val) => val,
+            Err(e) => return Err(format!("{}: {e}", src.display())),
+        };
+
+        ThemeIcon::new(&data, id).map_err(|e| format!("{}: {e}", src.display()))
+    }
+
+    /// Prepares icon image `data` to be installed as `<id>.<ext>`. PNG icons are also resized into all standard sizes
+    /// that are not larger than the source image. `id` must be created with `icon_id`.
+    pub fn new(data: &[u8], id: &str) -> Result<ThemeIcon, String> {
+        if id.is_empty() || id.starts_with('.') || id.contains('/') {
+            return Err(format!("invalid icon id {id}"));
+        }
+
+        let (format, size) = match detect_icon(data) {
+            Some(val) => val,
+            None => {
+                return Err(String::from(
+                    "unsupported icon format, expected PNG, SVG or XPM",
+                ))
+            }
+        };
+        let ext = format.get_ext();
+
+        let size_dir = match size {
+            Some((width, height)) => format!("{width}x{height}"),
+            None => String::from("scalable"),
+        };
+        let theme_size =
+            size.is_none_or(|(width, height)| width == height && HICOLOR_SIZES.contains(&width));
+
+        let mut files = Vec::new();
+        // PNG icons of other sizes are only installed as resized variants since the theme does not have such directories
+        if theme_size {
+            files.push((size_dir.clone(), ext, data.to_vec()));
+        } else if format != IconFormat::Png {
+            return Err(format!(
+                "{size_dir} is not a standard icon size, expected one of: {}",
+                HICOLOR_SIZES.map(|i| i.to_string()).join(", ")
+            ));
+        }
+
+        // Generate icons for other sizes so menus do not have to scale the source image
+        if format == IconFormat::Png {
+            for (dir, icon) in resize_icon(data)? {
+                if dir != size_dir {
+                    files.push((dir, ext, icon));
+                }
+            }
+        }
+
+        // Images smaller than the smallest size have no variants, the entry would point at a missing icon
+        if files.is_empty() {
+            let min = HICOLOR_SIZES[0];
+            return Err(format!(
+                "{size_dir} is smaller than the smallest icon size {min}x{min}"
+            ));
+        }
+
+        Ok(ThemeIcon {
+            id: id.to_string(),
+            files,
+        })
+    }
+
+    /// Returns icon name that should be used as the `Icon` key value.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Writes icon files into `$XDG_DATA_HOME/icons/hicolor/<size>/apps/<id>.<ext>`.
+    pub fn install(&self) -> Result<(), String> {
+        for (size_dir, ext, data) in &self.files {
+            let dir = data_home()
+                .join("icons/hicolor")
+                .join(size_dir)
+                .join("apps");
+            if let Err(e) = fs::create_dir_all(&dir) {
+                return Err(format!("{}: {e}", dir.display()));
+            }
+
+            let dest = dir.join(format!("{}.{ext}", self.id));
+            if let Err(e) = fs::write(&dest, data) {
+                return Err(format!("{}: {e}", dest.display()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn svg_detection() {
+        let svg = "<?xml version=\"1.0\"?>\n<!-- <html> -->\n<!DOCTYPE svg [<!ENTITY a \"b\">]>\n<svg xmlns=\"\">";
+        assert!(is_svg(svg));
+        assert!(is_svg("<svg:svg xmlns:svg=\"\"/>"));
+        assert!(!is_svg("<html><body><svg></svg></body></html>"));
+        assert!(!is_svg("<svgfoo/>"));
+
+        assert_eq!(icon_id("My App/../x"), format!("{ICON_PREFIX}My_App_.._x"));
+    }
+
+    #[test]
+    fn theme_icon_sizes() {
+        let png = |width, height| {
+            let mut data = Vec::new();
+            image::RgbaImage::new(width, height)
+                .write_to(
+                    &mut std::io::Cursor::new(&mut data),
+                    image::ImageFormat::Png,
+                )
+                .unwrap();
+            data
+        };
+        let dirs = |icon: ThemeIcon| -> Vec<String> {
+            icon.files.into_iter().map(|(dir, _, _)| dir).collect()
+        };
+
+        assert!(ThemeIcon::new(&png(10, 10), "mkdsk-tiny").is_err());
+        assert_eq!(
+            dirs(ThemeIcon::new(&png(16, 16), "mkdsk-small").unwrap()),
+            ["16x16"]
+        );
+        assert_eq!(
+            dirs(ThemeIcon::new(&png(20, 20), "mkdsk-odd").unwrap()),
+            ["16x16"]
+        );
+        assert!(dirs(ThemeIcon::new(&png(96, 96), "mkdsk-large").unwrap())
+            .contains(&String::from("96x96")));
+        assert!(ThemeIcon::new(&png(16, 16), "../x").is_err());
+    }
+}
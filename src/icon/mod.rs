@@ -0,0 +1,13 @@
+//! # icon
+//! This module contains functions that work with application icons.
+
+mod discover;
+mod favicon;
+mod install;
+mod resize;
+mod theme;
+
+pub use discover::discover_icon;
+pub use favicon::lookup_favicon;
+pub use install::{icon_id, ThemeIcon};
+pub use theme::lookup_icon;
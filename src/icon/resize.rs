@@ -0,0 +1,71 @@
+//! # resize
+//! Generates raster icons for the standard `hicolor` sizes from a single source image.
+
+use std::io::Cursor;
+
+use image::{imageops, imageops::FilterType, ImageFormat, RgbaImage};
+
+/// Sizes of the fixed size directories listed in the `hicolor` theme `index.theme`.
+pub const HICOLOR_SIZES: [u32; 13] = [16, 22, 24, 32, 36, 48, 64, 72, 96, 128, 192, 256, 512];
+
+/// Resizes PNG image into all standard `hicolor` sizes, including `@2` scaled variants.
+/// Sizes larger than the source image are skipped, so the source is never upscaled.
+///
+/// Returns a list of icon theme size directory names (e.g. `48x48@2`) and encoded PNG data.
+pub fn resize_icon(data: &[u8]) -> Result<Vec<(String, Vec<u8>)>, String> {
+    let source = match image::load_from_memory_with_format(data, ImageFormat::Png) {
+        Ok(val) => val.to_rgba8(),
+        Err(e) => return Err(e.to_string()),
+    };
+    let source_size = source.width().max(source.height());
+
+    let mut icons = Vec::new();
+    for size in HICOLOR_SIZES {
+        for scale in [1, 2] {
+            let pixels = size * scale;
+            if pixels > source_size {
+                continue;
+            }
+
+            let dir = match scale {
+                1 => format!("{size}x{size}"),
+                _ => format!("{size}x{size}@{scale}"),
+            };
+
+            icons.push((dir, encode_png(&fit_square(&source, pixels))?));
+        }
+    }
+
+    Ok(icons)
+}
+
+/// Scales image to fit into a `size`x`size` square. Non-square images are centered on a transparent background.
+fn fit_square(source: &RgbaImage, size: u32) -> RgbaImage {
+    let scale = size as f64 / source.width().max(source.height()) as f64;
+    let width = ((source.width() as f64 * scale).round() as u32).clamp(1, size);
+    let height = ((source.height() as f64 * scale).round() as u32).clamp(1, size);
+
+    let resized = imageops::resize(source, width, height, FilterType::Lanczos3);
+    if width == size && height == size {
+        return resized;
+    }
+
+    let mut canvas = RgbaImage::new(size, size);
+    imageops::overlay(
+        &mut canvas,
+        &resized,
+        ((size - width) / 2) as i64,
+        ((size - height) / 2) as i64,
+    );
+
+    canvas
+}
+
+fn encode_png(image: &RgbaImage) -> Result<Vec<u8>, String> {
+    let mut data = Vec::new();
+
+    match image.write_to(&mut Cursor::new(&mut data), ImageFormat::Png) {
+        Ok(_) => Ok(data),
+        Err(e) => Err(e.to_string()),
+    }
+}
@@ -0,0 +1,366 @@
+//! # theme
+//! Implements icon lookup as described in the freedesktop icon theme specification.
+//!
+//! See: https://specifications.freedesktop.org/icon-theme-spec/latest/
+
+use std::path::PathBuf;
+
+use crate::keyfile::KeyFile;
+use crate::xdg::{config_home, data_dirs, data_home, home_dir};
+
+/// Name of the fallback theme. Every icon theme implicitly inherits it.
+const FALLBACK_THEME: &str = "hicolor";
+
+/// Icon file extensions in the order of preference.
+const ICON_EXTENSIONS: [&str; 3] = ["png", "svg", "xpm"];
+
+#[derive(PartialEq)]
+enum DirType {
+    Fixed,
+    Scalable,
+    Threshold,
+}
+
+/// Subdirectory of the icon theme described in `index.theme`.
+struct ThemeDir {
+    path: String,
+    size: u32,
+    scale: u32,
+    dir_type: DirType,
+    min_size: u32,
+    max_size: u32,
+    threshold: u32,
+}
+
+impl ThemeDir {
+    fn matches_size(&self, size: u32, scale: u32) -> bool {
+        if self.scale != scale {
+            return false;
+        }
+
+        match self.dir_type {
+            DirType::Fixed => self.size == size,
+            DirType::Scalable => self.min_size <= size && size <= self.max_size,
+            DirType::Threshold => {
+                self.size.saturating_sub(self.threshold) <= size
+                    && size <= self.size + self.threshold
+            }
+        }
+    }
+
+    fn size_distance(&self, size: u32, scale: u32) -> u32 {
+        let size = size * scale;
+
+        match self.dir_type {
+            DirType::Fixed => (self.size * self.scale).abs_diff(size),
+            DirType::Scalable => {
+                let min = self.min_size * self.scale;
+                let max = self.max_size * self.scale;
+
+                min.saturating_sub(size) + size.saturating_sub(max)
+            }
+            DirType::Threshold => {
+                let min = self.size.saturating_sub(self.threshold) * self.scale;
+                let max = (self.size + self.threshold) * self.scale;
+
+                min.saturating_sub(size) + size.saturating_sub(max)
+            }
+        }
+    }
+}
+
+/// Icon theme loaded from its `index.theme`.
+struct Theme {
+    name: String,
+    inherits: Vec<String>,
+    dirs: Vec<ThemeDir>,
+}
+
+impl Theme {
+    /// Loads theme from the first `index.theme` found in the `base_dirs`.
+    fn load(name: &str, base_dirs: &[PathBuf]) -> Option<Theme> {
+        let index = base_dirs
+            .iter()
+            .map(|i| i.join(name).join("index.theme"))
+            .find(|i| i.is_file())?;
+        let index = KeyFile::load(&index)?;
+
+        let list = |key: &str| -> Vec<String> {
+            index
+                .get("Icon Theme", key)
+                .unwrap_or_default()
+                .split(',')
+                .map(|i| i.trim().to_string())
+                .filter(|i| !i.is_empty())
+                .collect()
+        };
+
+        let mut dir_names = list("Directories");
+        dir_names.append(&mut list("ScaledDirectories"));
+
+        let mut dirs = Vec::new();
+        for path in dir_names {
+            let get = |key: &str| index.get(&path, key).and_then(|i| i.parse::<u32>().ok());
+
+            let size = match get("Size") {
+                Some(val) => val,
+                None => continue,
+            };
+            let dir_type = match index.get(&path, "Type") {
+                Some("Fixed") => DirType::Fixed,
+                Some("Scalable") => DirType::Scalable,
+                _ => DirType::Threshold,
+            };
+
+            dirs.push(ThemeDir {
+                size,
+                scale: get("Scale").unwrap_or(1),
+                dir_type,
+                min_size: get("MinSize").unwrap_or(size),
+                max_size: get("MaxSize").unwrap_or(size),
+                threshold: get("Threshold").unwrap_or(2),
+                path,
+            });
+        }
+
+        Some(Theme {
+            name: name.to_string(),
+            inherits: list("Inherits"),
+            dirs,
+        })
+    }
+
+    /// Looks up the icon in this theme only. Exact size matches are preferred, otherwise the closest size is used.
+    fn lookup(&self, icon: &str, size: u32, scale: u32, base_dirs: &[PathBuf]) -> Option<PathBuf> {
+        let find = |dir: &ThemeDir| -> Option<PathBuf> {
+            for base in base_dirs {
+                for ext in ICON_EXTENSIONS {
+                    let file = base
+                        .join(&self.name)
+                        .join(&dir.path)
+                        .join(format!("{icon}.{ext}"));
+                    if file.is_file() {
+                        return Some(file);
+                    }
+                }
+            }
+            None
+        };
+
+        for dir in self.dirs.iter().filter(|i| i.matches_size(size, scale)) {
+            if let Some(file) = find(dir) {
+                return Some(file);
+            }
+        }
+
+        let mut closest: Option<(u32, PathBuf)> = None;
+        for dir in &self.dirs {
+            let distance = dir.size_distance(size, scale);
+            if closest.as_ref().is_some_and(|(i, _)| *i <= distance) {
+                continue;
+            }
+            if let Some(file) = find(dir) {
+                closest = Some((distance, file));
+            }
+        }
+
+        closest.map(|(_, file)| file)
+    }
+}
+
+/// Returns icon theme base directories in the order of lookup.
+fn base_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![home_dir().join(".icons"), data_home().join("icons")];
+    dirs.extend(data_dirs().into_iter().map(|i| i.join("icons")));
+    dirs.push(PathBuf::from("/usr/share/pixmaps"));
+
+    dirs
+}
+
+/// Returns name of the user's icon theme. Reads GTK and KDE settings, falls back to `hicolor`.
+fn current_theme() -> String {
+    let config = config_home();
+
+    let gtk = ["gtk-4.0/settings.ini", "gtk-3.0/settings.ini"]
+        .into_iter()
+        .filter_map(|i| KeyFile::load(&config.join(i)))
+        .find_map(|i| i.get("Settings", "gtk-icon-theme-name").map(String::from));
+    let kde = KeyFile::load(&config.join("kdeglobals"))
+        .and_then(|i| i.get("Icons", "Theme").map(String::from));
+
+    gtk.or(kde)
+        .filter(|i| !i.is_empty())
+        .unwrap_or(FALLBACK_THEME.to_string())
+}
+
+/// Looks up `icon` with the given `size` and `scale` in the user's icon theme, its parents and `hicolor`.
+/// Falls back to icons placed directly in the base directories (e.g. `/usr/share/pixmaps`).
+pub fn lookup_icon(icon: &str, size: u32, scale: u32) -> Option<PathBuf> {
+    find_icon(&base_dirs(), &current_theme(), icon, size, scale)
+}
+
+/// Looks up `icon` in the `theme` stored in the `base_dirs`. See `lookup_icon`.
+fn find_icon(
+    base_dirs: &[PathBuf],
+    theme: &str,
+    icon: &str,
+    size: u32,
+    scale: u32,
+) -> Option<PathBuf> {
+    let mut visited = Vec::new();
+
+    if let Some(file) = lookup_in_theme(base_dirs, theme, icon, size, scale, &mut visited) {
+        return Some(file);
+    }
+    if let Some(file) = lookup_in_theme(base_dirs, FALLBACK_THEME, icon, size, scale, &mut visited)
+    {
+        return Some(file);
+    }
+
+    for base in base_dirs {
+        for ext in ICON_EXTENSIONS {
+            let file = base.join(format!("{icon}.{ext}"));
+            if file.is_file() {
+                return Some(file);
+            }
+        }
+    }
+
+    None
+}
+
+/// Looks up `icon` in the `theme` and recursively in the themes it inherits.
+fn lookup_in_theme(
+    base_dirs: &[PathBuf],
+    theme: &str,
+    icon: &str,
+    size: u32,
+    scale: u32,
+    visited: &mut Vec<String>,
+) -> Option<PathBuf> {
+    if visited.iter().any(|i| i == theme) {
+        return None;
+    }
+    visited.push(theme.to_string());
+
+    let theme = Theme::load(theme, base_dirs)?;
+
+    if let Some(file) = theme.lookup(icon, size, scale, base_dirs) {
+        return Some(file);
+    }
+
+    for parent in &theme.inherits {
+        if let Some(file) = lookup_in_theme(base_dirs, parent, icon, size, scale, visited) {
+            return Some(file);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    #[test]
+    fn theme_lookup() {
+        let root = std::env::temp_dir().join(format!("mkdsk-test-{}-theme", std::process::id()));
+        let base = root.join("icons");
+        let pixmaps = root.join("pixmaps");
+
+        let files = [
+            (
+                "Child/index.theme",
+                "[Icon Theme]\nInherits=Parent\nDirectories=16x16/apps,22x22/apps\n\
+                 [16x16/apps]\nSize=16\nType=Fixed\n\
+                 [22x22/apps]\nSize=22\nType=Threshold\nThreshold=2\n",
+            ),
+            (
+                "Parent/index.theme",
+                "[Icon Theme]\nInherits=Child\nDirectories=48x48/apps,256x256/apps,scalable/apps\n\
+                 ScaledDirectories=32x32@2/apps\n\
+                 [48x48/apps]\nSize=48\nType=Fixed\n\
+                 [256x256/apps]\nSize=256\nType=Fixed\n\
+                 [scalable/apps]\nSize=64\nMinSize=64\nMaxSize=128\nType=Scalable\n\
+                 [32x32@2/apps]\nSize=32\nScale=2\nType=Fixed\n",
+            ),
+            (
+                "hicolor/index.theme",
+                "[Icon Theme]\nDirectories=64x64/apps\n[64x64/apps]\nSize=64\nType=Fixed\n",
+            ),
+            ("Child/16x16/apps/app.png", ""),
+            ("Child/22x22/apps/threshold.png", ""),
+            ("Parent/48x48/apps/app.png", ""),
+            ("Parent/48x48/apps/large.png", ""),
+            ("Parent/256x256/apps/large.png", ""),
+            ("Parent/scalable/apps/vector.svg", ""),
+            ("Parent/32x32@2/apps/scaled.png", ""),
+            ("Parent/48x48/apps/scaled.png", ""),
+            ("hicolor/64x64/apps/fallback.png", ""),
+        ];
+        for (path, text) in files {
+            let path = base.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, text).unwrap();
+        }
+        fs::create_dir_all(&pixmaps).unwrap();
+        fs::write(pixmaps.join("pixmap.xpm"), "").unwrap();
+
+        let base_dirs = [base.clone(), pixmaps.clone()];
+        let find = |icon, size, scale| {
+            find_icon(&base_dirs, "Child", icon, size, scale)
+                .map(|i| i.strip_prefix(&root).unwrap().to_string_lossy().to_string())
+        };
+
+        // Exact size of the theme is preferred over the parent theme
+        assert_eq!(
+            find("app", 16, 1).as_deref(),
+            Some("icons/Child/16x16/apps/app.png")
+        );
+        // Closest size of the theme is preferred over the exact size of the parent theme
+        assert_eq!(
+            find("app", 48, 1).as_deref(),
+            Some("icons/Child/16x16/apps/app.png")
+        );
+        assert_eq!(
+            find("threshold", 24, 1).as_deref(),
+            Some("icons/Child/22x22/apps/threshold.png")
+        );
+        // Inherited theme, closest size and scalable directories
+        assert_eq!(
+            find("large", 200, 1).as_deref(),
+            Some("icons/Parent/256x256/apps/large.png")
+        );
+        assert_eq!(
+            find("large", 100, 1).as_deref(),
+            Some("icons/Parent/48x48/apps/large.png")
+        );
+        assert_eq!(
+            find("vector", 96, 1).as_deref(),
+            Some("icons/Parent/scalable/apps/vector.svg")
+        );
+        assert_eq!(
+            find("scaled", 32, 2).as_deref(),
+            Some("icons/Parent/32x32@2/apps/scaled.png")
+        );
+        assert_eq!(
+            find("scaled", 48, 1).as_deref(),
+            Some("icons/Parent/48x48/apps/scaled.png")
+        );
+        // hicolor and base directory fallbacks
+        assert_eq!(
+            find("fallback", 16, 1).as_deref(),
+            Some("icons/hicolor/64x64/apps/fallback.png")
+        );
+        assert_eq!(find("pixmap", 16, 1).as_deref(), Some("pixmaps/pixmap.xpm"));
+        assert_eq!(find("missing", 16, 1), None);
+        assert_eq!(
+            find_icon(&base_dirs, "Missing", "fallback", 16, 1),
+            Some(base.join("hicolor/64x64/apps/fallback.png"))
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
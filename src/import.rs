@@ -0,0 +1,303 @@
+//! # import
+//! Imports Windows shortcuts (`.lnk` and `.url` files).
+//!
+//! Windows paths are translated to the paths of the Wine prefix set by `WINEPREFIX`, `~/.wine` is used by default.
+
+use std::{
+    env, fs,
+    io::Cursor,
+    path::{Path, PathBuf},
+};
+
+use image::ImageFormat;
+
+use crate::handler::{finalize, install, validate_source};
+use crate::keyfile::KeyFile;
+use crate::lnk::Lnk;
+use crate::shortcut::{quote_exec, Shortcut};
+use crate::source::pe::{Pe, WINE_COMMAND};
+use crate::xdg::home_dir;
+
+/// Windows environment variables and their values in the Wine prefix.
+const WINDOWS_VARIABLES: [(&str, &str); 9] = [
+    ("SystemDrive", "C:"),
+    ("SystemRoot", "C:\\windows"),
+    ("windir", "C:\\windows"),
+    ("ProgramFiles", "C:\\Program Files"),
+    ("ProgramFiles(x86)", "C:\\Program Files (x86)"),
+    ("ProgramData", "C:\\ProgramData"),
+    ("USERPROFILE", "C:\\users\\{user}"),
+    ("APPDATA", "C:\\users\\{user}\\AppData\\Roaming"),
+    ("LOCALAPPDATA", "C:\\users\\{user}\\AppData\\Local"),
+];
+
+/// Creates desktop entry from the Windows shortcut at `path` in the current directory.
+/// Shortcut is skipped if user does not want to overwrite existing file.
+pub fn import(path: &Path) -> Result<(), String> {
+    let ext = path
+        .extension()
+        .map(|i| i.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    let mut shortcut = match ext.as_str() {
+        "lnk" => import_lnk(path)?,
+        "url" => import_url(path)?,
+        _ => return Err(String::from("not a .lnk or .url file")),
+    };
+
+    // Shortcut name is the name of the file on Windows
+    shortcut.set_name(path.file_stem().unwrap().to_string_lossy().to_string());
+
+    let installs = finalize(&mut shortcut).map_err(|e| e.message)?;
+
+    // Shortcut is skipped if user does not want to overwrite existing file
+    if !shortcut.confirm_overwrite().map_err(|e| e.message)? {
+        return Ok(());
+    }
+
+    install(&installs).map_err(|e| e.message)?;
+    shortcut.create().map_err(|e| e.message)
+}
+
+/// Returns `Shortcut` of the Shell Link file.
+fn import_lnk(path: &Path) -> Result<Shortcut, String> {
+    let data = fs::read(path).map_err(|e| e.to_string())?;
+    let lnk = Lnk::parse(&data)?;
+
+    let target = match (&lnk.target, &lnk.relative_path) {
+        (Some(target), _) => windows_path(target),
+        (None, Some(relative)) => Some(resolve_path(
+            path.parent().unwrap(),
+            &relative.replace('\\', "/"),
+        )),
+        (None, None) => None,
+    }
+    .ok_or(String::from("shortcut target can not be found"))?;
+
+    let mut shortcut = new_shortcut(&target)?;
+    if let Some(args) = lnk.arguments {
+        shortcut.set_args(exec_args(&args));
+    }
+    if let Some(working_dir) = lnk.working_dir.and_then(|i| windows_path(&i)) {
+        shortcut.set_path(working_dir.to_string_lossy().to_string());
+    }
+    if let Some(comment) = lnk.name {
+        shortcut.set_comment(comment);
+    }
+    if let Some(icon) = lnk.icon_location.and_then(|i| windows_path(&i)) {
+        set_icon(&icon, &mut shortcut);
+    }
+
+    Ok(shortcut)
+}
+
+/// Returns `Shortcut` of the Internet Shortcut file.
+fn import_url(path: &Path) -> Result<Shortcut, String> {
+    let url = KeyFile::load(path).ok_or(String::from("can not read file"))?;
+
+    let target = url
+        .get("InternetShortcut", "URL")
+        .ok_or(String::from("shortcut has no URL"))?;
+
+    let mut shortcut = new_shortcut(Path::new(target))?;
+    if let Some(icon) = url
+        .get("InternetShortcut", "IconFile")
+        .and_then(windows_path)
+    {
+        set_icon(&icon, &mut shortcut);
+    }
+
+    Ok(shortcut)
+}
+
+/// Returns `Shortcut` of the source. Windows executables are run in the Wine prefix the paths are translated to.
+fn new_shortcut(source: &Path) -> Result<Shortcut, String> {
+    let source = source.to_string_lossy().to_string();
+    if source.starts_with('/') && !Path::new(&source).exists() {
+        return Err(format!("shortcut target does not exist {source}"));
+    }
+
+    let mut shortcut = Shortcut::new();
+    validate_source(source, &mut shortcut).map_err(|e| e.message)?;
+
+    if shortcut
+        .get_interpreter()
+        .is_some_and(|i| i == WINE_COMMAND)
+    {
+        shortcut.set_wine_prefix(wine_prefix());
+    }
+
+    Ok(shortcut)
+}
+
+/// Uses icon of the `.ico` file or the icon stored in the executable.
+fn set_icon(icon: &Path, shortcut: &mut Shortcut) {
+    let ext = icon
+        .extension()
+        .map(|i| i.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    let png = match ext.as_str() {
+        "ico" => image::open(icon).map_err(|e| e.to_string()).and_then(|i| {
+            let mut png = Vec::new();
+            i.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+                .map_err(|e| e.to_string())?;
+            Ok(png)
+        }),
+        "exe" | "dll" => Pe::read(icon).map(|i| i.icon.unwrap_or_default()),
+        _ => return,
+    };
+
+    match png {
+        Ok(val) if !val.is_empty() => shortcut.set_embedded_icon(val),
+        Ok(_) => (),
+        Err(e) => println!("mkdsk: warning: {}: {e}", icon.display()),
+    }
+}
+
+/// Returns Wine prefix the Windows paths are translated to.
+fn wine_prefix() -> PathBuf {
+    match env::var_os("WINEPREFIX") {
+        Some(val) if !val.is_empty() => PathBuf::from(val),
+        _ => home_dir().join(".wine"),
+    }
+}
+
+/// Translates Windows path (e.g. `%ProgramFiles%\App\app.exe`) to the path in the Wine prefix.
+/// Returns `None` if the path is not absolute or its drive is not mapped.
+fn windows_path(path: &str) -> Option<PathBuf> {
+    let user = env::var("USER").unwrap_or_default();
+
+    let mut path = path.to_string();
+    for (name, value) in WINDOWS_VARIABLES {
+        let variable = format!("%{name}%");
+        if let Some(pos) = path
+            .to_ascii_lowercase()
+            .find(&variable.to_ascii_lowercase())
+        {
+            path.replace_range(pos..pos + variable.len(), &value.replace("{user}", &user));
+        }
+    }
+    let path = path.replace('\\', "/");
+
+    let drive = match path.as_bytes() {
+        [drive, b':', ..] if drive.is_ascii_alphabetic() => drive.to_ascii_lowercase() as char,
+        _ => return None,
+    };
+
+    // Drives are symlinks in dosdevices, drive C: may be missing in the prefixes that were not started yet
+    let prefix = wine_prefix();
+    let root = fs::canonicalize(prefix.join("dosdevices").join(format!("{drive}:")))
+        .ok()
+        .or_else(|| (drive == 'c').then(|| prefix.join("drive_c")))?;
+
+    Some(resolve_path(&root, &path[2..]))
+}
+
+/// Converts Windows command line arguments to the arguments of the `Exec` key.
+fn exec_args(args: &str) -> String {
+    windows_args(args)
+        .iter()
+        .map(|i| match i.is_empty() {
+            true => String::from("\"\""),
+            false => quote_exec(i),
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Splits Windows command line arguments the same way `CommandLineToArgvW` does.
+/// Backslashes are literal unless they precede a quote.
+fn windows_args(args: &str) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut arg: Option<String> = None;
+    let mut quoted = false;
+    let mut backslashes = 0;
+
+    for i in args.chars() {
+        match i {
+            '\\' => {
+                backslashes += 1;
+                continue;
+            }
+            '"' => {
+                let arg = arg.get_or_insert_with(String::new);
+                arg.push_str(&"\\".repeat(backslashes / 2));
+                // Odd number of backslashes escapes the quote
+                if backslashes % 2 == 1 {
+                    arg.push('"');
+                } else {
+                    quoted = !quoted;
+                }
+            }
+            _ => {
+                if backslashes > 0 {
+                    let arg = arg.get_or_insert_with(String::new);
+                    arg.push_str(&"\\".repeat(backslashes));
+                }
+
+                match (i == ' ' || i == '\t') && !quoted {
+                    true => result.extend(arg.take()),
+                    false => arg.get_or_insert_with(String::new).push(i),
+                }
+            }
+        }
+        backslashes = 0;
+    }
+
+    if backslashes > 0 {
+        arg.get_or_insert_with(String::new)
+            .push_str(&"\\".repeat(backslashes));
+    }
+    result.extend(arg);
+
+    result
+}
+
+/// Joins `path` to `root` matching components case-insensitively, the same way Windows does.
+fn resolve_path(root: &Path, path: &str) -> PathBuf {
+    let mut result = root.to_path_buf();
+
+    for name in path.split('/').filter(|i| !i.is_empty() && *i != ".") {
+        let found = fs::read_dir(&result).ok().and_then(|entries| {
+            entries
+                .flatten()
+                .map(|i| i.file_name())
+                .find(|i| i.to_string_lossy().eq_ignore_ascii_case(name))
+        });
+
+        match (name, found) {
+            ("..", _) => {
+                result.pop();
+            }
+            (_, Some(found)) => result.push(found),
+            (_, None) => result.push(name),
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_line_arguments() {
+        assert_eq!(
+            windows_args(r#"--flag "a b"  C:\dir\ "" x"y z"w"#),
+            ["--flag", "a b", r"C:\dir\", "", "xy zw"]
+        );
+        assert_eq!(
+            windows_args(r#"a\\"b c" d\"e \\\"f"#),
+            [r"a\b c", r#"d"e"#, r#"\"f"#]
+        );
+        assert_eq!(windows_args(r"\ a \\"), [r"\", "a", r"\\"]);
+        assert!(windows_args("  ").is_empty());
+
+        assert_eq!(
+            exec_args(r#"--name "a b" 100% "" %1"#),
+            r#"--name "a b" 100%% "" %%1"#
+        );
+    }
+}
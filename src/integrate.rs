@@ -0,0 +1,285 @@
+//! # integrate
+//! Keeps launchers of the AppImages stored in a directory up to date.
+//!
+//! Launchers are written to `$XDG_DATA_HOME/applications` and marked with `OWNERSHIP_KEY`, so only launchers created by
+//! mkdsk are updated or removed.
+
+use std::{
+    ffi::OsStr,
+    fs::{self, Permissions},
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+};
+
+use inotify::{EventMask, Inotify, WatchMask};
+
+use crate::icon::{icon_id, ThemeIcon};
+use crate::keyfile::KeyFile;
+use crate::settings::*;
+use crate::shortcut::{file_id, EntryType, Shortcut};
+use crate::source::appimage::AppImage;
+use crate::xdg::data_home;
+
+/// Creates launchers for all AppImages in `dir`, then watches `dir` and updates launchers when AppImages are added,
+/// replaced or deleted. Returns only if watching fails.
+pub fn integrate(dir: &Path) -> Result<(), String> {
+    let apps = data_home().join("applications");
+    let hicolor = data_home().join("icons/hicolor");
+    if let Err(e) = fs::create_dir_all(&apps) {
+        return Err(format!("{}: {e}", apps.display()));
+    }
+
+    // Start watching before the initial sync so no changes are missed
+    let mut inotify = Inotify::init().map_err(|e| e.to_string())?;
+    inotify
+        .watches()
+        .add(
+            dir,
+            WatchMask::CLOSE_WRITE
+                | WatchMask::MOVED_TO
+                | WatchMask::MOVED_FROM
+                | WatchMask::DELETE
+                | WatchMask::ATTRIB,
+        )
+        .map_err(|e| format!("{}: {e}", dir.display()))?;
+
+    // Remove launchers of AppImages deleted while mkdsk was not running
+    for (launcher, source) in owned_launchers(&apps) {
+        if source.parent() == Some(dir) && !source.exists() {
+            remove_launcher(&launcher, &source, &hicolor);
+        }
+    }
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            sync_appimage(&entry.path(), &apps);
+        }
+    }
+
+    println!("mkdsk: watching {}", dir.display());
+
+    let mut buffer = [0u8; 4096];
+    loop {
+        let events = inotify
+            .read_events_blocking(&mut buffer)
+            .map_err(|e| e.to_string())?;
+
+        for event in events {
+            let path = match event.name {
+                Some(name) => dir.join(name),
+                None => continue,
+            };
+
+            if event
+                .mask
+                .intersects(EventMask::DELETE | EventMask::MOVED_FROM)
+            {
+                for (launcher, source) in owned_launchers(&apps) {
+                    if source == path {
+                        remove_launcher(&launcher, &source, &hicolor);
+                    }
+                }
+            } else {
+                sync_appimage(&path, &apps);
+            }
+        }
+    }
+}
+
+/// Creates or updates launcher of the AppImage at `path`. Files that are not AppImages are ignored.
+fn sync_appimage(path: &Path, apps: &Path) {
+    if !path.is_file() {
+        return;
+    }
+
+    let appimage = match AppImage::read(path) {
+        Ok(Some(val)) => val,
+        Ok(None) => return,
+        Err(e) => {
+            println!("mkdsk: warning: {}: {e}", path.display());
+            return;
+        }
+    };
+
+    let id = launcher_id(path);
+    let launcher = apps.join(format!("{id}.desktop"));
+
+    // Launcher with the same id may be written by the user or belong to the AppImage of another directory
+    if launcher.exists()
+        && KeyFile::load(&launcher)
+            .and_then(|i| i.get("Desktop Entry", OWNERSHIP_KEY).map(PathBuf::from))
+            .is_none_or(|i| i != path)
+    {
+        println!(
+            "mkdsk: warning: {} is not created for {}, skipping",
+            launcher.display(),
+            path.display()
+        );
+        return;
+    }
+
+    // Downloaded AppImages are usually not executable. Mode is set only if needed, since it triggers another event
+    let mode = match path.metadata() {
+        Ok(val) => val.permissions().mode(),
+        Err(e) => {
+            println!("mkdsk: warning: {}: {e}", path.display());
+            return;
+        }
+    };
+    if mode & 0o111 == 0 {
+        if let Err(e) = fs::set_permissions(path, Permissions::from_mode(mode | 0o111)) {
+            println!(
+                "mkdsk: warning: can not make {} executable: {e}",
+                path.display()
+            );
+            return;
+        }
+    }
+
+    let mut shortcut = Shortcut::new();
+    shortcut.set_entry_type(EntryType::Application);
+    shortcut.set_out(apps.to_path_buf());
+    shortcut.set_exec(path.to_path_buf());
+    shortcut.set_name(appimage_name(path).to_string());
+    shortcut.set_owner_source(OWNERSHIP_KEY, path.to_path_buf());
+    appimage.apply(&mut shortcut);
+
+    // Icons are named after the launcher, so they can be removed together with it
+    if let Some(icon) = &appimage.icon {
+        match ThemeIcon::new(icon, &icon_id(appimage_name(path)))
+            .and_then(|i| i.install().map(|_| i))
+        {
+            Ok(val) => shortcut.set_icon(val.id().to_string()),
+            Err(e) => println!("mkdsk: warning: {}: {e}", path.display()),
+        }
+    }
+
+    let text = shortcut.to_desktop_entry();
+    let status = match fs::read_to_string(&launcher) {
+        Ok(val) if val == text => return,
+        Ok(_) => "updated",
+        Err(_) => "created",
+    };
+
+    match fs::write(&launcher, text) {
+        Ok(_) => println!("mkdsk: {status} {}", launcher.display()),
+        Err(e) => println!("mkdsk: warning: {}: {e}", launcher.display()),
+    }
+}
+
+/// Removes launcher and its icons installed into the `hicolor` directory.
+fn remove_launcher(launcher: &Path, source: &Path, hicolor: &Path) {
+    if let Err(e) = fs::remove_file(launcher) {
+        println!("mkdsk: warning: {}: {e}", launcher.display());
+        return;
+    }
+
+    let id = icon_id(appimage_name(source));
+    if let Ok(sizes) = fs::read_dir(hicolor) {
+        for size in sizes.flatten() {
+            for ext in ["png", "svg", "xpm"] {
+                let _ = fs::remove_file(size.path().join("apps").join(format!("{id}.{ext}")));
+            }
+        }
+    }
+
+    println!("mkdsk: removed {}", launcher.display());
+}
+
+/// Returns paths and sources of the launchers created by mkdsk.
+fn owned_launchers(apps: &Path) -> Vec<(PathBuf, PathBuf)> {
+    let entries = match fs::read_dir(apps) {
+        Ok(val) => val,
+        Err(_) => return Vec::new(),
+    };
+
+    entries
+        .flatten()
+        .map(|i| i.path())
+        .filter(|i| i.extension().is_some_and(|ext| ext == "desktop"))
+        .filter_map(|i| {
+            let source = KeyFile::load(&i)?
+                .get("Desktop Entry", OWNERSHIP_KEY)
+                .map(PathBuf::from)?;
+            Some((i, source))
+        })
+        .collect()
+}
+
+/// Returns name of the AppImage, i.e. the file name without the extension.
+fn appimage_name(appimage: &Path) -> &str {
+    appimage
+        .file_stem()
+        .and_then(OsStr::to_str)
+        .unwrap_or_default()
+}
+
+/// Returns desktop file id of the AppImage launcher.
+fn launcher_id(appimage: &Path) -> String {
+    format!(
+        "{INTEGRATE_FILE_PREFIX}{}",
+        file_id(appimage_name(appimage))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn launcher_files() {
+        let appimage = Path::new("/apps/My App-1.2.AppImage");
+        assert_eq!(appimage_name(appimage), "My App-1.2");
+        assert_eq!(
+            launcher_id(appimage),
+            format!("{INTEGRATE_FILE_PREFIX}My_App-1.2")
+        );
+
+        let root =
+            std::env::temp_dir().join(format!("mkdsk-test-{}-integrate", std::process::id()));
+        let apps = root.join("applications");
+        let hicolor = root.join("hicolor");
+        fs::create_dir_all(&apps).unwrap();
+
+        let entry = |source: &str| format!("[Desktop Entry]\nName=App\n{OWNERSHIP_KEY}={source}\n");
+        let launcher = apps.join(format!("{}.desktop", launcher_id(appimage)));
+        fs::write(&launcher, entry("/apps/My App-1.2.AppImage")).unwrap();
+        fs::write(apps.join("user.desktop"), "[Desktop Entry]\nName=User\n").unwrap();
+        fs::write(
+            apps.join("mkdsk-editor.desktop"),
+            format!("[Desktop Entry]\nName=Editor\n{MANIFEST_OWNERSHIP_KEY}=/tmp/apps.toml\n"),
+        )
+        .unwrap();
+        fs::write(apps.join("notes.txt"), entry("/apps/Notes.AppImage")).unwrap();
+        fs::write(
+            apps.join("group.desktop"),
+            format!("[Desktop Entry]\nName=Group\n[Other]\n{OWNERSHIP_KEY}=/apps/x\n"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            owned_launchers(&apps),
+            [(launcher.clone(), appimage.to_path_buf())]
+        );
+
+        // Icons of the launcher are removed in all sizes, icons of other launchers are kept
+        let icon = |size: &str, name: &str| hicolor.join(size).join("apps").join(name);
+        let id = icon_id(appimage_name(appimage));
+        let icons = [
+            icon("48x48", &format!("{id}.png")),
+            icon("scalable", &format!("{id}.svg")),
+        ];
+        let other = icon("48x48", &format!("{id}-other.png"));
+        for i in icons.iter().chain([&other]) {
+            fs::create_dir_all(i.parent().unwrap()).unwrap();
+            fs::write(i, "").unwrap();
+        }
+
+        remove_launcher(&launcher, appimage, &hicolor);
+        assert!(!launcher.exists());
+        assert!(icons.iter().all(|i| !i.exists()));
+        assert!(other.exists());
+        assert!(owned_launchers(&apps).is_empty());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
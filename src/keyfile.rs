@@ -0,0 +1,87 @@
+//! # keyfile
+//! Contains a parser for freedesktop key files such as `.desktop` files and icon theme `index.theme` files.
+
+use std::{fs, path::Path};
+
+/// Parsed key file. Groups and keys are stored in the same order as in the file.
+pub struct KeyFile {
+    groups: Vec<(String, Vec<(String, String)>)>,
+}
+
+impl KeyFile {
+    /// Parses key file text. Comments, blank lines and keys outside of any group are ignored.
+    pub fn parse(text: &str) -> KeyFile {
+        let mut groups: Vec<(String, Vec<(String, String)>)> = Vec::new();
+
+        for line in text.lines() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line.starts_with('[') && line.ends_with(']') {
+                groups.push((line[1..line.len() - 1].to_string(), Vec::new()));
+                continue;
+            }
+
+            if let (Some((key, value)), Some((_, entries))) =
+                (line.split_once('='), groups.last_mut())
+            {
+                entries.push((key.trim().to_string(), value.trim().to_string()));
+            }
+        }
+
+        KeyFile { groups }
+    }
+
+    /// Reads and parses key file at `path`. Returns `None` if the file can not be read.
+    pub fn load(path: &Path) -> Option<KeyFile> {
+        let data = fs::read(path).ok()?;
+
+        Some(KeyFile::parse(&String::from_utf8_lossy(&data)))
+    }
+
+    /// Returns value of the `key` in the `group`.
+    pub fn get(&self, group: &str, key: &str) -> Option<&str> {
+        self.groups
+            .iter()
+            .filter(|(name, _)| name == group)
+            .flat_map(|(_, entries)| entries.iter())
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns items of the `key` that is a list of strings separated with semicolons. Escaped semicolons are kept in
+    /// the items.
+    pub fn get_list(&self, group: &str, key: &str) -> Option<Vec<String>> {
+        let mut items = split_list(self.get(group, key)?, &[';']);
+        items.retain(|i| !i.is_empty());
+
+        Some(items)
+    }
+}
+
+/// Splits list value into items. Escaped `separators` and backslashes are unescaped, other escape sequences are kept.
+pub fn split_list(value: &str, separators: &[char]) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut item = String::new();
+    let mut chars = value.chars();
+    while let Some(i) = chars.next() {
+        match i {
+            '\\' => match chars.next() {
+                Some(next) if next == '\\' || separators.contains(&next) => item.push(next),
+                Some(next) => {
+                    item.push(i);
+                    item.push(next);
+                }
+                None => item.push(i),
+            },
+            i if separators.contains(&i) => items.push(std::mem::take(&mut item)),
+            _ => item.push(i),
+        }
+    }
+    items.push(item);
+
+    items
+}
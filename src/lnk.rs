@@ -0,0 +1,306 @@
+//! # lnk
+//! Reads and writes Windows Shell Link (`.lnk`) files.
+//!
+//! See: https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-shllink
+
+use std::path::Path;
+
+use crate::bytes::{u16_at, u32_at, utf16_string};
+
+const HEADER_SIZE: usize = 0x4c;
+const LINK_CLSID: &[u8; 16] = b"\x01\x14\x02\x00\x00\x00\x00\x00\xc0\x00\x00\x00\x00\x00\x00\x46";
+
+const HAS_LINK_TARGET_ID_LIST: u32 = 0x1;
+const HAS_LINK_INFO: u32 = 0x2;
+const HAS_NAME: u32 = 0x4;
+const HAS_RELATIVE_PATH: u32 = 0x8;
+const HAS_WORKING_DIR: u32 = 0x10;
+const HAS_ARGUMENTS: u32 = 0x20;
+const HAS_ICON_LOCATION: u32 = 0x40;
+const IS_UNICODE: u32 = 0x80;
+
+const FILE_ATTRIBUTE_DIRECTORY: u32 = 0x10;
+const FILE_ATTRIBUTE_NORMAL: u32 = 0x80;
+/// Show command of the target window.
+const SW_SHOWNORMAL: u32 = 1;
+/// Drive type of the volume the target is stored on.
+const DRIVE_FIXED: u32 = 3;
+
+/// Link info flag that indicates that the local base path is present.
+const VOLUME_ID_AND_LOCAL_BASE_PATH: u32 = 0x1;
+/// Link info header size starting from which the unicode paths are present.
+const LINK_INFO_UNICODE_HEADER_SIZE: usize = 0x24;
+
+/// Windows shortcut. Paths are Windows paths.
+#[derive(Default)]
+pub struct Lnk {
+    /// Absolute path of the target.
+    pub target: Option<String>,
+    /// Path of the target relative to the `.lnk` file.
+    pub relative_path: Option<String>,
+    pub working_dir: Option<String>,
+    pub arguments: Option<String>,
+    pub icon_location: Option<String>,
+    /// Description of the shortcut.
+    pub name: Option<String>,
+    /// Target is a directory.
+    pub is_dir: bool,
+}
+
+impl Lnk {
+    /// Parses Shell Link file.
+    pub fn parse(data: &[u8]) -> Result<Lnk, String> {
+        let invalid = || String::from("not a Shell Link file");
+
+        if u32_at(data, 0) != Some(HEADER_SIZE as u32) || data.get(4..20) != Some(LINK_CLSID) {
+            return Err(invalid());
+        }
+        let flags = u32_at(data, 0x14).ok_or_else(invalid)?;
+
+        let mut pos = HEADER_SIZE;
+        if flags & HAS_LINK_TARGET_ID_LIST != 0 {
+            pos += 2 + u16_at(data, pos).ok_or_else(invalid)? as usize;
+        }
+
+        let mut lnk = Lnk {
+            is_dir: u32_at(data, 0x18).ok_or_else(invalid)? & FILE_ATTRIBUTE_DIRECTORY != 0,
+            ..Default::default()
+        };
+        if flags & HAS_LINK_INFO != 0 {
+            let info = data.get(pos..).ok_or_else(invalid)?;
+            lnk.target = link_info_path(info);
+            pos += u32_at(info, 0).ok_or_else(invalid)? as usize;
+        }
+
+        // String data is stored in the order of the flags
+        let unicode = flags & IS_UNICODE != 0;
+        for flag in [
+            HAS_NAME,
+            HAS_RELATIVE_PATH,
+            HAS_WORKING_DIR,
+            HAS_ARGUMENTS,
+            HAS_ICON_LOCATION,
+        ] {
+            if flags & flag == 0 {
+                continue;
+            }
+
+            let len = u16_at(data, pos).ok_or_else(invalid)? as usize;
+            let size = match unicode {
+                true => len * 2,
+                false => len,
+            };
+            let text = data.get(pos + 2..pos + 2 + size).ok_or_else(invalid)?;
+            pos += 2 + size;
+
+            let text = Some(match unicode {
+                true => utf16_string(text),
+                false => ansi_string(text),
+            })
+            .filter(|i| !i.is_empty());
+
+            match flag {
+                HAS_NAME => lnk.name = text,
+                HAS_RELATIVE_PATH => lnk.relative_path = text,
+                HAS_WORKING_DIR => lnk.working_dir = text,
+                HAS_ARGUMENTS => lnk.arguments = text,
+                _ => lnk.icon_location = text,
+            }
+        }
+
+        Ok(lnk)
+    }
+
+    /// Returns content of the Shell Link file. Strings are stored as UTF-16.
+    pub fn write(&self) -> Vec<u8> {
+        let strings = [
+            (HAS_NAME, &self.name),
+            (HAS_RELATIVE_PATH, &self.relative_path),
+            (HAS_WORKING_DIR, &self.working_dir),
+            (HAS_ARGUMENTS, &self.arguments),
+            (HAS_ICON_LOCATION, &self.icon_location),
+        ];
+
+        let mut flags = IS_UNICODE;
+        if self.target.is_some() {
+            flags |= HAS_LINK_INFO;
+        }
+        for (flag, text) in strings {
+            if text.is_some() {
+                flags |= flag;
+            }
+        }
+        let attributes = match self.is_dir {
+            true => FILE_ATTRIBUTE_DIRECTORY,
+            false => FILE_ATTRIBUTE_NORMAL,
+        };
+
+        // Header: size, CLSID, flags, attributes, times, file size, icon index, show command, hotkey and reserved
+        let mut data = Vec::new();
+        data.extend_from_slice(&(HEADER_SIZE as u32).to_le_bytes());
+        data.extend_from_slice(LINK_CLSID);
+        data.extend_from_slice(&flags.to_le_bytes());
+        data.extend_from_slice(&attributes.to_le_bytes());
+        data.extend_from_slice(&[0; 32]);
+        data.extend_from_slice(&SW_SHOWNORMAL.to_le_bytes());
+        data.extend_from_slice(&[0; 12]);
+
+        if let Some(target) = &self.target {
+            data.append(&mut link_info(target));
+        }
+
+        for text in strings.into_iter().filter_map(|(_, text)| text.as_ref()) {
+            let text: Vec<u16> = text.encode_utf16().collect();
+            data.extend_from_slice(&(text.len() as u16).to_le_bytes());
+            text.iter()
+                .for_each(|i| data.extend_from_slice(&i.to_le_bytes()));
+        }
+
+        // Terminal block of the extra data
+        data.extend_from_slice(&[0; 4]);
+
+        data
+    }
+}
+
+/// Converts path to the path in the Wine prefix. Files outside of `drive_c` are stored on the drive `Z:`.
+pub fn to_windows_path(path: &Path) -> String {
+    let path = path.to_string_lossy();
+
+    let (drive, rest) = match path.split_once("/drive_c/") {
+        Some((_, rest)) => ("C:", rest),
+        None => ("Z:", path.trim_start_matches('/')),
+    };
+
+    format!("{drive}\\{}", rest.replace('/', "\\"))
+}
+
+/// Returns local path of the link info structure at the start of `info`.
+fn link_info_path(info: &[u8]) -> Option<String> {
+    let header_size = u32_at(info, 4)? as usize;
+    let flags = u32_at(info, 8)?;
+    if flags & VOLUME_ID_AND_LOCAL_BASE_PATH == 0 {
+        return None;
+    }
+
+    let string_at = |offset_pos: usize, unicode: bool| -> Option<String> {
+        let data = info.get(u32_at(info, offset_pos)? as usize..)?;
+        Some(match unicode {
+            true => utf16_string(data),
+            false => ansi_string(data),
+        })
+    };
+
+    let (base, suffix) = match header_size >= LINK_INFO_UNICODE_HEADER_SIZE {
+        true => (string_at(0x1c, true)?, string_at(0x20, true)?),
+        false => (string_at(0x10, false)?, string_at(0x18, false)?),
+    };
+
+    match base.ends_with('\\') || suffix.is_empty() {
+        true => Some(base + &suffix),
+        false => Some(format!("{base}\\{suffix}")),
+    }
+}
+
+/// Returns link info structure with the local path of the target.
+fn link_info(target: &str) -> Vec<u8> {
+    let volume_id = [
+        0x11u32.to_le_bytes(),
+        DRIVE_FIXED.to_le_bytes(),
+        [0; 4],
+        0x10u32.to_le_bytes(),
+    ]
+    .concat();
+
+    // ANSI path is required, characters outside of ASCII are replaced
+    let mut ansi: Vec<u8> = target
+        .chars()
+        .map(|i| match i.is_ascii() {
+            true => i as u8,
+            false => b'?',
+        })
+        .collect();
+    ansi.push(0);
+    let mut unicode: Vec<u8> = target
+        .encode_utf16()
+        .chain([0])
+        .flat_map(|i| i.to_le_bytes())
+        .collect();
+
+    let volume_id_pos = LINK_INFO_UNICODE_HEADER_SIZE;
+    let base_pos = volume_id_pos + volume_id.len() + 1;
+    let suffix_pos = base_pos + ansi.len();
+    let unicode_base_pos = suffix_pos + 1;
+    let unicode_suffix_pos = unicode_base_pos + unicode.len();
+    let size = unicode_suffix_pos + 2;
+
+    let mut info = Vec::new();
+    for i in [
+        size,
+        LINK_INFO_UNICODE_HEADER_SIZE,
+        VOLUME_ID_AND_LOCAL_BASE_PATH as usize,
+        volume_id_pos,
+        base_pos,
+        0,
+        suffix_pos,
+        unicode_base_pos,
+        unicode_suffix_pos,
+    ] {
+        info.extend_from_slice(&(i as u32).to_le_bytes());
+    }
+    info.extend_from_slice(&volume_id);
+    // Empty volume label
+    info.push(0);
+    info.append(&mut ansi);
+    // Empty path suffixes
+    info.push(0);
+    info.append(&mut unicode);
+    info.extend_from_slice(&[0; 2]);
+
+    info
+}
+
+/// Decodes null-terminated string in the system code page. Latin-1 is assumed.
+fn ansi_string(data: &[u8]) -> String {
+    data.iter()
+        .take_while(|i| **i != 0)
+        .map(|i| *i as char)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lnk_round_trip() {
+        let lnk = Lnk {
+            target: Some(String::from("Z:\\opt\\app\\app.exe")),
+            working_dir: Some(String::from("Z:\\opt\\app")),
+            arguments: Some(String::from("--flag \"a b\"")),
+            icon_location: Some(String::from("Z:\\opt\\app\\app.ico")),
+            name: Some(String::from("Приложение")),
+            ..Default::default()
+        };
+
+        let parsed = Lnk::parse(&lnk.write()).unwrap();
+        assert_eq!(parsed.target, lnk.target);
+        assert_eq!(parsed.working_dir, lnk.working_dir);
+        assert_eq!(parsed.arguments, lnk.arguments);
+        assert_eq!(parsed.icon_location, lnk.icon_location);
+        assert_eq!(parsed.name, lnk.name);
+        assert_eq!(parsed.relative_path, None);
+        assert!(!parsed.is_dir);
+
+        let dir = Lnk {
+            target: Some(String::from("C:\\users")),
+            is_dir: true,
+            ..Default::default()
+        };
+        let parsed = Lnk::parse(&dir.write()).unwrap();
+        assert_eq!(parsed.target, dir.target);
+        assert!(parsed.is_dir);
+
+        assert!(Lnk::parse(b"not a link").is_err());
+    }
+}
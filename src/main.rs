@@ -1,30 +1,69 @@
 // main.rs
 
+mod apply;
+mod bytes;
+mod categories;
+mod config;
+mod custom_keys;
+mod desktops;
+mod error;
 mod handler;
+mod icon;
+mod import;
+mod integrate;
+mod keyfile;
+mod lnk;
+mod menu;
 mod settings;
 mod shortcut;
+mod source;
+mod webapp;
+mod xdg;
 
 use std::process::exit;
 
+use config::config_options;
 use handler::*;
 use settings::*;
 use shortcut::Shortcut;
 
 fn main() {
-    let mut args = std::env::args();
-    args.next();
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
 
-    if args.len() == 0 {
+    // Subcommands take precedence, source with the name of a subcommand is passed after `--`
+    let is_source = args.first().is_some_and(|i| i == "--");
+    if is_source {
+        args.remove(0);
+    }
+
+    if args.is_empty() {
         print_help();
         return;
     }
 
+    match args[ENTRY_SOURCE_INDEX].as_str() {
+        "--help" | "-h" => {
+            print_help();
+            return;
+        }
+        "--version" | "-v" => {
+            print_version();
+            return;
+        }
+        _ => (),
+    }
+
+    if !is_source && run_subcommand(&args) {
+        return;
+    }
+
     let mut shortcut = Shortcut::new();
 
-    let mut iter = args.enumerate();
+    let mut options: Vec<(String, String)> = Vec::new();
+    let mut iter = args.into_iter().enumerate();
     while let Some((i, arg)) = iter.next() {
         if i == ENTRY_SOURCE_INDEX {
-            validate_source(arg, &mut shortcut);
+            validate_source(arg, &mut shortcut).unwrap_or_else(|e| e.exit());
         } else {
             let (_, option_val) = match iter.next() {
                 Some(val) => val,
@@ -33,9 +72,35 @@ fn main() {
                     exit(4)
                 }
             };
-            validate_option(arg.clone(), option_val, &mut shortcut);
+            options.push((arg, option_val));
         }
     }
 
-    shortcut.create();
+    // Profile is not an entry option, the last provided profile is used
+    let profile = options
+        .iter()
+        .rfind(|(i, _)| i == "--profile")
+        .map(|(_, i)| i.clone());
+    options.retain(|(i, _)| i != "--profile");
+
+    let defaults = match config_options(profile.as_deref(), &options) {
+        Ok(val) => val,
+        Err(e) => {
+            println!("mkdsk: {e}");
+            exit(47)
+        }
+    };
+    for (option, option_val) in defaults.into_iter().chain(options) {
+        validate_option(&option, &option_val, &mut shortcut).unwrap_or_else(|e| e.exit());
+    }
+
+    let installs = finalize(&mut shortcut).unwrap_or_else(|e| e.exit());
+
+    // Nothing is installed if user does not want to overwrite existing file
+    if !shortcut.confirm_overwrite().unwrap_or_else(|e| e.exit()) {
+        return;
+    }
+
+    install(&installs).unwrap_or_else(|e| e.exit());
+    shortcut.create().unwrap_or_else(|e| e.exit());
 }
@@ -0,0 +1,146 @@
+//! # menu
+//! Creates application menu submenus.
+//!
+//! Each submenu is described by a `.directory` file and a `.menu` file merged into the applications menu. Submenu
+//! includes entries with its own category.
+//!
+//! See: https://specifications.freedesktop.org/menu-spec/latest/
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::settings::MENU_FILE_PREFIX;
+use crate::shortcut::{EntryType, Shortcut};
+use crate::xdg::{config_home, data_home};
+
+/// Returns category of the entries shown in the submenu with the given name.
+pub fn submenu_category(name: &str) -> String {
+    format!("X-{}", submenu_id(name))
+}
+
+/// Creates or updates submenu with the given name.
+pub fn create_submenu(name: &str) -> Result<(), String> {
+    for (path, text) in submenu_files(name, &data_home(), &config_home()) {
+        write_file(&path, &text)?;
+    }
+
+    Ok(())
+}
+
+/// Returns paths and contents of the `.directory` and `.menu` files of the submenu.
+fn submenu_files(name: &str, data_home: &Path, config_home: &Path) -> [(PathBuf, String); 2] {
+    let id = submenu_id(name);
+    let category = submenu_category(name);
+    let file_id = format!("{MENU_FILE_PREFIX}{}", id.to_lowercase());
+
+    // Name of the submenu shown by the menu
+    let directories = data_home.join("desktop-directories");
+    let mut directory = Shortcut::new();
+    directory.set_entry_type(EntryType::Directory);
+    directory.set_out(directories.clone());
+    directory.set_name(name.to_string());
+
+    let menu = format!(
+        "<!DOCTYPE Menu PUBLIC \"-//freedesktop//DTD Menu 1.0//EN\"\n \
+         \"http://www.freedesktop.org/standards/menu-spec/1.0/menu.dtd\">\n\
+         <Menu>\n\
+         \t<Name>Applications</Name>\n\
+         \t<Menu>\n\
+         \t\t<Name>{}</Name>\n\
+         \t\t<Directory>{file_id}.directory</Directory>\n\
+         \t\t<Include>\n\
+         \t\t\t<Category>{category}</Category>\n\
+         \t\t</Include>\n\
+         \t</Menu>\n\
+         </Menu>\n",
+        escape_xml(name)
+    );
+
+    [
+        (
+            directories.join(format!("{file_id}.directory")),
+            directory.to_desktop_entry(),
+        ),
+        (
+            config_home
+                .join("menus/applications-merged")
+                .join(format!("{file_id}.menu")),
+            menu,
+        ),
+    ]
+}
+
+fn submenu_id(name: &str) -> String {
+    name.chars()
+        .map(|i| match i.is_ascii_alphanumeric() {
+            true => i,
+            false => '-',
+        })
+        .collect()
+}
+
+/// Writes `text` to `path` if the file content differs. Parent directories are created.
+fn write_file(path: &Path, text: &str) -> Result<(), String> {
+    if fs::read_to_string(path).is_ok_and(|i| i == text) {
+        return Ok(());
+    }
+
+    fs::create_dir_all(path.parent().unwrap())
+        .and_then(|_| fs::write(path, text))
+        .map_err(|e| format!("{}: {e}", path.display()))
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn submenu() {
+        let name = "Dev & <Tools>";
+        assert_eq!(submenu_category(name), "X-Dev----Tools-");
+
+        let [(directory, directory_text), (menu, menu_text)] =
+            submenu_files(name, Path::new("/data"), Path::new("/config"));
+
+        assert_eq!(
+            directory,
+            Path::new("/data/desktop-directories")
+                .join(format!("{MENU_FILE_PREFIX}dev----tools-.directory"))
+        );
+        assert_eq!(
+            directory_text,
+            "[Desktop Entry]\nType=Directory\nName=Dev & <Tools>\n"
+        );
+
+        assert_eq!(
+            menu,
+            Path::new("/config/menus/applications-merged")
+                .join(format!("{MENU_FILE_PREFIX}dev----tools-.menu"))
+        );
+        assert_eq!(
+            menu_text,
+            format!(
+                "<!DOCTYPE Menu PUBLIC \"-//freedesktop//DTD Menu 1.0//EN\"\n \
+                 \"http://www.freedesktop.org/standards/menu-spec/1.0/menu.dtd\">\n\
+                 <Menu>\n\
+                 \t<Name>Applications</Name>\n\
+                 \t<Menu>\n\
+                 \t\t<Name>Dev &amp; &lt;Tools&gt;</Name>\n\
+                 \t\t<Directory>{MENU_FILE_PREFIX}dev----tools-.directory</Directory>\n\
+                 \t\t<Include>\n\
+                 \t\t\t<Category>X-Dev----Tools-</Category>\n\
+                 \t\t</Include>\n\
+                 \t</Menu>\n\
+                 </Menu>\n"
+            )
+        );
+    }
+}
@@ -12,3 +12,30 @@ pub const PROGRAM_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 /// Link to the program's github page.
 pub const PROGRAM_GITHUB: &str = "https://github.com/Clovis1444/mkdsk";
+
+/// Icon size used to check if the icon can be found in the icon theme.
+pub const DEFAULT_ICON_SIZE: u32 = 48;
+
+/// Key that marks entries managed by `mkdsk integrate`. Its value is the path to the entry source.
+pub const OWNERSHIP_KEY: &str = "X-Mkdsk-Source";
+
+/// Key that marks entries managed by `mkdsk apply`. Its value is the path to the manifest of the entry.
+pub const MANIFEST_OWNERSHIP_KEY: &str = "X-Mkdsk-Manifest";
+
+/// Directory watched by `mkdsk integrate` if no directory was provided. Relative to the home directory.
+pub const DEFAULT_APPIMAGE_DIR: &str = "Applications";
+
+/// Prefix of the file names of launchers created by `mkdsk integrate`.
+pub const INTEGRATE_FILE_PREFIX: &str = "mkdsk-";
+
+/// Directory of the Firefox profiles used by web apps. Relative to `$XDG_DATA_HOME`.
+pub const WEBAPP_PROFILE_DIR: &str = "mkdsk/webapps";
+
+/// Config file with the default options. Relative to `$XDG_CONFIG_HOME`.
+pub const CONFIG_FILE: &str = "mkdsk/config.toml";
+
+/// Prefix of the icons installed for the entries.
+pub const ICON_PREFIX: &str = "mkdsk-";
+
+/// Prefix of the file names of submenu `.menu` and `.directory` files.
+pub const MENU_FILE_PREFIX: &str = "mkdsk-";
@@ -1,37 +1,65 @@
 //! # shortcut
 //! This module contains `Shortcut` structure that contains information about the `.desktop` file that will be created.
 
-use std::{fs::File, io::Write, path::PathBuf, process::exit};
+use std::{
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::custom_keys::{group_text, CustomKey, MAIN_GROUP};
+use crate::error::Error;
+use crate::lnk::{to_windows_path, Lnk};
 
 /// Contains information about the `.desktop` file that will be created.
 pub struct Shortcut {
     out: Option<PathBuf>,
     entry_type: EntryType,
-    _version: String,
+    spec_version: Option<SpecVersion>,
     name: String,
     generic_name: String,
-    no_display: bool,
+    no_display: Option<bool>,
     comment: String,
     icon: String,
-    hidden: bool,
-    only_shown_in: String,
-    not_shown_in: String,
-    d_bus_activatable: bool,
+    hidden: Option<bool>,
+    only_shown_in: Vec<String>,
+    not_shown_in: Vec<String>,
+    d_bus_activatable: Option<bool>,
     try_exec: String,
     exec: Option<PathBuf>,
     args: String,
     path: String,
-    terminal: bool,
-    actions: String,
-    mime_type: String,
-    categories: String,
-    implements: String,
-    keywords: String,
-    startup_notify: bool,
+    terminal: Option<bool>,
+    actions: Vec<String>,
+    mime_type: Vec<String>,
+    categories: Vec<String>,
+    /// Lists are read from the source and are replaced by the first list option.
+    mime_type_prefilled: bool,
+    categories_prefilled: bool,
+    implements: Vec<String>,
+    keywords: Vec<String>,
+    startup_notify: Option<bool>,
     startup_wm_class: String,
     url: String,
-    prefers_non_default_gpu: bool,
-    single_main_window: bool,
+    prefers_non_default_gpu: Option<bool>,
+    single_main_window: Option<bool>,
+    install_icon: Option<PathBuf>,
+    auto_icon: bool,
+    embedded_icon: Option<Vec<u8>>,
+    owner_source: Option<(&'static str, PathBuf)>,
+    custom_keys: Vec<CustomKey>,
+    interpreter: Option<String>,
+    chmod: bool,
+    java: Option<PathBuf>,
+    wine: Option<PathBuf>,
+    wine_prefix: Option<PathBuf>,
+    format: Format,
+    webapp: bool,
+    file_manager: bool,
+    submenu: Option<String>,
+    current_desktop_only: bool,
+    explicit_defaults: bool,
 }
 
 impl Shortcut {
@@ -39,101 +67,144 @@ impl Shortcut {
         Shortcut {
             out: None,
             entry_type: EntryType::Application,
-            _version: String::new(),
+            spec_version: None,
             name: String::new(),
             generic_name: String::new(),
-            no_display: false,
+            no_display: None,
             comment: String::new(),
             icon: String::new(),
-            hidden: false,
-            only_shown_in: String::new(),
-            not_shown_in: String::new(),
-            d_bus_activatable: false,
+            hidden: None,
+            only_shown_in: Vec::new(),
+            not_shown_in: Vec::new(),
+            d_bus_activatable: None,
             try_exec: String::new(),
             exec: None,
             args: String::new(),
             path: String::new(),
-            terminal: false,
-            actions: String::new(),
-            mime_type: String::new(),
-            categories: String::new(),
-            implements: String::new(),
-            keywords: String::new(),
-            startup_notify: false,
+            terminal: None,
+            actions: Vec::new(),
+            mime_type: Vec::new(),
+            categories: Vec::new(),
+            mime_type_prefilled: false,
+            categories_prefilled: false,
+            implements: Vec::new(),
+            keywords: Vec::new(),
+            startup_notify: None,
             startup_wm_class: String::new(),
             url: String::new(),
-            prefers_non_default_gpu: false,
-            single_main_window: false,
+            prefers_non_default_gpu: None,
+            single_main_window: None,
+            install_icon: None,
+            auto_icon: true,
+            embedded_icon: None,
+            owner_source: None,
+            custom_keys: Vec::new(),
+            interpreter: None,
+            chmod: false,
+            java: None,
+            wine: None,
+            wine_prefix: None,
+            format: Format::Desktop,
+            webapp: false,
+            file_manager: false,
+            submenu: None,
+            current_desktop_only: false,
+            explicit_defaults: false,
+        }
+    }
+
+    /// Creates .desktop file, passes all `Shortcut data` to it. Other formats are used if `format` is set.
+    /// Existing file is replaced, use `confirm_overwrite` to ask the user first.
+    pub fn create(&self) -> Result<(), Error> {
+        let text = self.contents().map_err(|e| Error::new(34, e))?;
+
+        let mut file = File::create(self.file_path()).map_err(|e| Error::new(5, e.to_string()))?;
+
+        // Write to file
+        file.write_all(&text)
+            .map_err(|e| Error::new(6, e.to_string()))
+    }
+
+    /// Returns `true` if the file does not exist yet or the user agreed to replace it.
+    pub fn confirm_overwrite(&self) -> Result<bool, Error> {
+        if !self.file_path().exists() {
+            return Ok(true);
         }
+
+        self.ask_user_overwrite()
+    }
+
+    /// Returns content of the created file in the `format`.
+    pub fn contents(&self) -> Result<Vec<u8>, String> {
+        match self.format {
+            Format::Desktop => Ok(self.to_desktop_entry().into_bytes()),
+            Format::Url => self.to_url_file().map(String::into_bytes),
+            Format::Webloc => self.to_webloc().map(String::into_bytes),
+            Format::Lnk => self.to_lnk(),
+        }
+    }
+
+    /// Returns path of the created file.
+    pub fn file_path(&self) -> PathBuf {
+        self.out.clone().unwrap().join(self.file_name())
     }
 
-    /// Creates .desktop file, passes all `Shortcut data` to it.
-    /// This function will close the program if file creation fails.
+    /// Returns content of the .desktop file.
     /// Edit this function if you are plannig to add new `Shortcut` fields.
-    pub fn create(&self) {
+    pub fn to_desktop_entry(&self) -> String {
         assert!(&self.out.is_some(), "out must be set");
         assert!(!&self.name.is_empty(), "name must be set");
         match self.entry_type {
-            EntryType::Application | EntryType::Directory => {
-                assert!(&self.exec.is_some(), "exec must be set")
-            }
+            EntryType::Application => assert!(&self.exec.is_some(), "exec must be set"),
             EntryType::Link => assert!(!&self.url.is_empty(), "url must be set"),
+            EntryType::Directory => (),
         }
 
-        let mut file_name = self.name.clone();
-        file_name.push_str(".desktop");
-        let file_path = self.out.clone().unwrap().join(file_name);
-
-        // Check if file already exists
-        if file_path.exists() {
-            // Exit if user does not want to overwrite existing file
-            if !self.ask_user_overwrite() {
-                exit(0);
-            }
-        }
-
-        let mut file = match File::create(file_path.clone()) {
-            Ok(val) => val,
-            Err(e) => {
-                println!("mkdsk: {e}");
-                exit(5)
-            }
-        };
-
         // Keys that make sense in all contexts
-        let mut text = format!(
-            "[Desktop Entry]\nType={}\nName={}\n",
-            self.entry_type.get_str(),
-            self.name
-        );
+        let mut text = format!("[Desktop Entry]\nType={}\n", self.entry_type.get_str());
+        if let Some(version) = self.spec_version {
+            text.push_str(&format!("Version={}\n", version.get_str()));
+        }
+        text.push_str(&format!("Name={}\n", self.name));
         if !self.generic_name.is_empty() {
             text.push_str(&format!("GenericName={}\n", self.generic_name));
         }
-        text.push_str(&format!("NoDisplay={}\n", self.no_display.to_string()));
+        if let Some(val) = self.bool_value(self.no_display) {
+            text.push_str(&format!("NoDisplay={val}\n"));
+        }
         if !self.comment.is_empty() {
             text.push_str(&format!("Comment={}\n", self.comment));
         }
         if !self.icon.is_empty() {
             text.push_str(&format!("Icon={}\n", self.icon));
         }
-        text.push_str(&format!("Hidden={}\n", self.hidden.to_string()));
+        if let Some(val) = self.bool_value(self.hidden) {
+            text.push_str(&format!("Hidden={val}\n"));
+        }
         if !self.only_shown_in.is_empty() {
-            text.push_str(&format!("OnlyShownIn={}\n", self.only_shown_in));
+            text.push_str(&format!(
+                "OnlyShownIn={}\n",
+                list_value(&self.only_shown_in)
+            ));
         }
         if !self.not_shown_in.is_empty() {
-            text.push_str(&format!("NotShownIn={}\n", self.not_shown_in));
+            text.push_str(&format!("NotShownIn={}\n", list_value(&self.not_shown_in)));
         }
 
         // Apllication specific keys
         if self.entry_type == EntryType::Application {
-            text.push_str(&format!(
-                "DBusActivatable={}\n",
-                self.d_bus_activatable.to_string()
-            ));
+            if let Some(val) = self.bool_value(self.d_bus_activatable) {
+                if self.supports("DBusActivatable") {
+                    text.push_str(&format!("DBusActivatable={val}\n"));
+                }
+            }
             if !self.try_exec.is_empty() {
                 text.push_str(&format!("TryExec={}\n", self.try_exec));
             }
-            let mut exec = String::from(self.exec.clone().unwrap().to_str().unwrap());
+            let mut exec = quote_exec(self.exec.clone().unwrap().to_str().unwrap());
+            if let Some(interpreter) = &self.interpreter {
+                exec = format!("{interpreter} {exec}");
+            }
             if !self.args.is_empty() {
                 exec.push(' ');
                 exec.push_str(&self.args);
@@ -143,59 +214,224 @@ impl Shortcut {
             if !self.path.is_empty() {
                 text.push_str(&format!("Path={}\n", self.path));
             }
-            text.push_str(&format!("Terminal={}\n", self.terminal.to_string()));
-            if !self.actions.is_empty() {
-                text.push_str(&format!("Actions={}\n", self.actions));
+            if let Some(val) = self.bool_value(self.terminal) {
+                text.push_str(&format!("Terminal={val}\n"));
+            }
+            if !self.actions.is_empty() && self.supports("Actions") {
+                text.push_str(&format!("Actions={}\n", list_value(&self.actions)));
             }
             if !self.mime_type.is_empty() {
-                text.push_str(&format!("MimeType={}\n", self.mime_type));
+                text.push_str(&format!("MimeType={}\n", list_value(&self.mime_type)));
             }
             if !self.categories.is_empty() {
-                text.push_str(&format!("Categories={}\n", self.categories));
+                text.push_str(&format!("Categories={}\n", list_value(&self.categories)));
             }
-            if !self.implements.is_empty() {
-                text.push_str(&format!("Implements={}\n", self.implements));
+            if !self.implements.is_empty() && self.supports("Implements") {
+                text.push_str(&format!("Implements={}\n", list_value(&self.implements)));
             }
-            if !self.keywords.is_empty() {
-                text.push_str(&format!("Keywords={}\n", self.keywords));
+            if !self.keywords.is_empty() && self.supports("Keywords") {
+                text.push_str(&format!("Keywords={}\n", list_value(&self.keywords)));
+            }
+            if let Some(val) = self.bool_value(self.startup_notify) {
+                text.push_str(&format!("StartupNotify={val}\n"));
             }
-            text.push_str(&format!(
-                "StartupNotify={}\n",
-                self.startup_notify.to_string()
-            ));
             if !self.startup_wm_class.is_empty() {
                 text.push_str(&format!("StartupWMClass={}\n", self.startup_wm_class));
             }
-            text.push_str(&format!(
-                "PrefersNonDefaultGPU={}\n",
-                self.prefers_non_default_gpu.to_string()
-            ));
-            text.push_str(&format!(
-                "SingleMainWindow={}\n",
-                self.single_main_window.to_string()
-            ));
-        }
-        // Directory specific keys
-        else if self.entry_type == EntryType::Directory {
-            // TODO(clovis): fix Directory?
-            text.push_str(&format!(
-                "Exec={}\n",
-                self.exec.clone().unwrap().to_str().unwrap()
-            ));
+            if let Some(val) = self.bool_value(self.prefers_non_default_gpu) {
+                if self.supports("PrefersNonDefaultGPU") {
+                    text.push_str(&format!("PrefersNonDefaultGPU={val}\n"));
+                }
+            }
+            if let Some(val) = self.bool_value(self.single_main_window) {
+                if self.supports("SingleMainWindow") {
+                    text.push_str(&format!("SingleMainWindow={val}\n"));
+                }
+            }
         }
         // Link specific keys
         else if self.entry_type == EntryType::Link {
             text.push_str(&format!("URL={}\n", self.url))
         }
 
-        // Write to file
-        match file.write(text.as_bytes()) {
-            Ok(_) => (),
-            Err(e) => {
-                println!("mkdsk: {e}");
-                exit(6)
+        text.push_str(&group_text(&self.custom_keys, MAIN_GROUP));
+
+        if let Some((key, source)) = &self.owner_source {
+            text.push_str(&format!("{key}={}\n", source.to_str().unwrap()));
+        }
+
+        // Groups of the custom keys in the order they were set
+        let mut groups: Vec<&str> = Vec::new();
+        for i in &self.custom_keys {
+            // Action groups without the Actions key would be left unused
+            if i.action().is_some() && !self.supports("Actions") {
+                continue;
+            }
+            if i.group != MAIN_GROUP && !groups.contains(&i.group.as_str()) {
+                groups.push(&i.group);
             }
+        }
+        for group in groups {
+            text.push_str(&format!(
+                "\n[{group}]\n{}",
+                group_text(&self.custom_keys, group)
+            ));
+        }
+
+        text
+    }
+
+    /// Returns value of the boolean key that should be written. Keys that were not set are written only if
+    /// `explicit_defaults` is set.
+    fn bool_value(&self, value: Option<bool>) -> Option<bool> {
+        value.or(self.explicit_defaults.then_some(false))
+    }
+
+    /// Returns `false` if the `key` was introduced in a newer specification than the declared `spec_version`.
+    fn supports(&self, key: &str) -> bool {
+        let Some(version) = self.spec_version else {
+            return true;
         };
+
+        KEY_VERSIONS
+            .iter()
+            .find(|(name, _)| *name == key)
+            .is_none_or(|(_, required)| version >= *required)
+    }
+
+    /// Returns keys that are set but are not written because they are newer than the declared `spec_version`.
+    pub fn unsupported_keys(&self) -> Vec<(&'static str, SpecVersion)> {
+        if self.entry_type != EntryType::Application {
+            return Vec::new();
+        }
+
+        let set = [
+            ("DBusActivatable", self.d_bus_activatable.is_some()),
+            ("Actions", !self.actions.is_empty()),
+            ("Implements", !self.implements.is_empty()),
+            ("Keywords", !self.keywords.is_empty()),
+            (
+                "PrefersNonDefaultGPU",
+                self.prefers_non_default_gpu.is_some(),
+            ),
+            ("SingleMainWindow", self.single_main_window.is_some()),
+        ];
+
+        KEY_VERSIONS
+            .into_iter()
+            .filter(|(key, _)| !self.supports(key))
+            .filter(|(key, _)| set.iter().any(|(name, val)| name == key && *val))
+            .collect()
+    }
+
+    /// Returns content of the Windows Internet Shortcut (.url) file.
+    pub fn to_url_file(&self) -> Result<String, String> {
+        Ok(format!(
+            "[InternetShortcut]\r\nURL={}\r\n",
+            self.target_url()?
+        ))
+    }
+
+    /// Returns content of the macOS .webloc file.
+    pub fn to_webloc(&self) -> Result<String, String> {
+        let url = self
+            .target_url()?
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+
+        Ok(format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+             <plist version=\"1.0\">\n\
+             <dict>\n\
+             \t<key>URL</key>\n\
+             \t<string>{url}</string>\n\
+             </dict>\n\
+             </plist>\n"
+        ))
+    }
+
+    /// Returns content of the Windows Shell Link (.lnk) file. Paths are converted to the paths in the Wine prefix.
+    /// Links are opened with Explorer.
+    pub fn to_lnk(&self) -> Result<Vec<u8>, String> {
+        let mut lnk = Lnk {
+            name: Some(self.comment.clone()).filter(|i| !i.is_empty()),
+            ..Default::default()
+        };
+
+        match self.entry_type {
+            EntryType::Application => {
+                // Link info stores only local paths, so programs found in PATH are resolved
+                let exec = self.exec.clone().unwrap();
+                let exec = match exec.is_absolute() {
+                    true => exec,
+                    false => which::which(&exec).map_err(|e| format!("{}: {e}", exec.display()))?,
+                };
+                lnk.target = Some(to_windows_path(&exec));
+
+                // Field codes are expanded only by the desktop environment
+                let args: Vec<&str> = self
+                    .args
+                    .split_whitespace()
+                    .filter(|i| !(i.len() == 2 && i.starts_with('%') && *i != "%%"))
+                    .collect();
+                lnk.arguments = Some(args.join(" ").replace("%%", "%")).filter(|i| !i.is_empty());
+
+                if !self.path.is_empty() {
+                    lnk.working_dir = Some(to_windows_path(Path::new(&self.path)));
+                }
+            }
+            EntryType::Link => match &self.exec {
+                // Links to the folders point to the folder itself
+                Some(dir) => {
+                    lnk.target = Some(to_windows_path(dir));
+                    lnk.is_dir = true;
+                }
+                None => {
+                    lnk.target = Some(String::from("C:\\windows\\explorer.exe"));
+                    lnk.arguments = Some(self.url.clone());
+                }
+            },
+            EntryType::Directory => return Err(self.export_error()),
+        }
+
+        if self.icon.ends_with(".ico") && Path::new(&self.icon).is_absolute() {
+            lnk.icon_location = Some(to_windows_path(Path::new(&self.icon)));
+        }
+
+        Ok(lnk.write())
+    }
+
+    /// Returns URL the .url and .webloc files point to.
+    fn target_url(&self) -> Result<String, String> {
+        match self.entry_type {
+            EntryType::Link => Ok(self.url.clone()),
+            _ => Err(self.export_error()),
+        }
+    }
+
+    /// Returns error message for the entries that can not be exported to `format`.
+    fn export_error(&self) -> String {
+        format!(
+            "{} entries can not be exported to .{}",
+            self.entry_type.get_str(),
+            self.format.get_ext()
+        )
+    }
+
+    /// Returns desktop file id, i.e. the file name without the extension.
+    pub fn desktop_id(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns name of the created file.
+    fn file_name(&self) -> String {
+        match (&self.entry_type, self.format) {
+            // Menu directories are stored in the .directory files
+            (EntryType::Directory, Format::Desktop) => format!("{}.directory", self.name),
+            _ => format!("{}.{}", self.name, self.format.get_ext()),
+        }
     }
 
     pub fn set_exec(&mut self, exec: PathBuf) {
@@ -220,19 +456,19 @@ impl Shortcut {
         self.generic_name = generic_name;
     }
     pub fn set_no_display(&mut self, no_display: bool) {
-        self.no_display = no_display;
+        self.no_display = Some(no_display);
     }
     pub fn set_hidden(&mut self, hidden: bool) {
-        self.hidden = hidden;
+        self.hidden = Some(hidden);
     }
-    pub fn set_only_shown_in(&mut self, envs: String) {
-        self.only_shown_in = envs;
+    pub fn add_only_shown_in(&mut self, envs: Vec<String>) {
+        add_items(&mut self.only_shown_in, envs);
     }
-    pub fn set_not_shown_in(&mut self, envs: String) {
-        self.not_shown_in = envs;
+    pub fn add_not_shown_in(&mut self, envs: Vec<String>) {
+        add_items(&mut self.not_shown_in, envs);
     }
     pub fn set_d_bus_activatable(&mut self, d_bus_activatable: bool) {
-        self.d_bus_activatable = d_bus_activatable;
+        self.d_bus_activatable = Some(d_bus_activatable);
     }
     pub fn set_try_exec(&mut self, try_exec: String) {
         self.try_exec = try_exec;
@@ -241,46 +477,198 @@ impl Shortcut {
         self.path = path;
     }
     pub fn set_terminal(&mut self, use_terminal: bool) {
-        self.terminal = use_terminal;
+        self.terminal = Some(use_terminal);
     }
-    pub fn set_actions(&mut self, actions: String) {
-        self.actions = actions;
+    pub fn add_actions(&mut self, actions: Vec<String>) {
+        add_items(&mut self.actions, actions);
     }
     pub fn set_url(&mut self, url: String) {
         self.url = url
     }
     pub fn set_prefers_non_default_gpu(&mut self, prefers_non_default_gpu: bool) {
-        self.prefers_non_default_gpu = prefers_non_default_gpu
+        self.prefers_non_default_gpu = Some(prefers_non_default_gpu)
     }
     pub fn set_single_main_window(&mut self, single_main_window: bool) {
-        self.single_main_window = single_main_window
+        self.single_main_window = Some(single_main_window)
     }
     pub fn set_entry_type(&mut self, entry_type: EntryType) {
         self.entry_type = entry_type
     }
-    pub fn set_mime_type(&mut self, mime_type: String) {
-        self.mime_type = mime_type
+    pub fn prefill_mime_type(&mut self, mime_type: Vec<String>) {
+        self.mime_type = mime_type;
+        self.mime_type_prefilled = true;
     }
-    pub fn set_categories(&mut self, categories: String) {
-        self.categories = categories
+    pub fn add_mime_type(&mut self, mime_type: Vec<String>) {
+        if std::mem::take(&mut self.mime_type_prefilled) {
+            self.mime_type.clear();
+        }
+        add_items(&mut self.mime_type, mime_type);
+    }
+    pub fn prefill_categories(&mut self, categories: Vec<String>) {
+        self.categories = categories;
+        self.categories_prefilled = true;
     }
-    pub fn set_implements(&mut self, implements: String) {
-        self.implements = implements
+    pub fn add_categories(&mut self, categories: Vec<String>) {
+        if std::mem::take(&mut self.categories_prefilled) {
+            self.categories.clear();
+        }
+        add_items(&mut self.categories, categories);
     }
-    pub fn set_keywords(&mut self, keywords: String) {
-        self.keywords = keywords
+    /// Adds category without replacing the categories read from the source.
+    pub fn push_category(&mut self, category: String) {
+        add_items(&mut self.categories, vec![category]);
+    }
+    pub fn add_implements(&mut self, implements: Vec<String>) {
+        add_items(&mut self.implements, implements);
+    }
+    pub fn add_keywords(&mut self, keywords: Vec<String>) {
+        add_items(&mut self.keywords, keywords);
     }
     pub fn set_startup_notify(&mut self, startup_notify: bool) {
-        self.startup_notify = startup_notify;
+        self.startup_notify = Some(startup_notify);
     }
     pub fn set_startup_wm_class(&mut self, startup_wm_class: String) {
         self.startup_wm_class = startup_wm_class
     }
+    pub fn set_install_icon(&mut self, install_icon: PathBuf) {
+        self.install_icon = Some(install_icon);
+    }
+    pub fn set_auto_icon(&mut self, auto_icon: bool) {
+        self.auto_icon = auto_icon;
+    }
+    pub fn set_embedded_icon(&mut self, embedded_icon: Vec<u8>) {
+        self.embedded_icon = Some(embedded_icon);
+    }
+    /// Adds custom key. Value of the key that was already set is replaced in place. Ids of the action groups are added
+    /// to the actions.
+    pub fn add_custom_key(&mut self, custom_key: CustomKey) {
+        if let Some(action) = custom_key.action() {
+            add_items(&mut self.actions, vec![action.to_string()]);
+        }
+        match self
+            .custom_keys
+            .iter_mut()
+            .find(|i| i.group == custom_key.group && i.key == custom_key.key)
+        {
+            Some(val) => *val = custom_key,
+            None => self.custom_keys.push(custom_key),
+        }
+    }
+    /// Marks the entry as managed by mkdsk: `key` is written with the path to the `owner_source`.
+    pub fn set_owner_source(&mut self, key: &'static str, owner_source: PathBuf) {
+        self.owner_source = Some((key, owner_source));
+    }
+    pub fn set_interpreter(&mut self, interpreter: Option<String>) {
+        self.interpreter = interpreter;
+    }
+    pub fn set_chmod(&mut self, chmod: bool) {
+        self.chmod = chmod;
+    }
+    pub fn set_java(&mut self, java: PathBuf) {
+        self.java = Some(java);
+    }
+    pub fn set_wine(&mut self, wine: PathBuf) {
+        self.wine = Some(wine);
+    }
+    pub fn set_wine_prefix(&mut self, wine_prefix: PathBuf) {
+        self.wine_prefix = Some(wine_prefix);
+    }
+    pub fn set_format(&mut self, format: Format) {
+        self.format = format;
+    }
+    pub fn set_webapp(&mut self, webapp: bool) {
+        self.webapp = webapp;
+    }
+    pub fn set_file_manager(&mut self, file_manager: bool) {
+        self.file_manager = file_manager;
+    }
+    pub fn set_spec_version(&mut self, spec_version: SpecVersion) {
+        self.spec_version = Some(spec_version);
+    }
+    pub fn set_submenu(&mut self, submenu: String) {
+        self.submenu = Some(submenu);
+    }
+    pub fn set_explicit_defaults(&mut self, explicit_defaults: bool) {
+        self.explicit_defaults = explicit_defaults;
+    }
+    pub fn set_current_desktop_only(&mut self, current_desktop_only: bool) {
+        self.current_desktop_only = current_desktop_only;
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+    pub fn get_install_icon(&self) -> Option<&PathBuf> {
+        self.install_icon.as_ref()
+    }
+    pub fn get_icon(&self) -> &str {
+        &self.icon
+    }
+    pub fn get_exec(&self) -> Option<&PathBuf> {
+        self.exec.as_ref()
+    }
+    pub fn get_auto_icon(&self) -> bool {
+        self.auto_icon
+    }
+    pub fn get_embedded_icon(&self) -> Option<&Vec<u8>> {
+        self.embedded_icon.as_ref()
+    }
+    pub fn get_interpreter(&self) -> Option<&String> {
+        self.interpreter.as_ref()
+    }
+    pub fn get_chmod(&self) -> bool {
+        self.chmod
+    }
+    pub fn get_java(&self) -> Option<&PathBuf> {
+        self.java.as_ref()
+    }
+    pub fn get_wine(&self) -> Option<&PathBuf> {
+        self.wine.as_ref()
+    }
+    pub fn get_wine_prefix(&self) -> Option<&PathBuf> {
+        self.wine_prefix.as_ref()
+    }
+    pub fn get_webapp(&self) -> bool {
+        self.webapp
+    }
+    pub fn get_file_manager(&self) -> bool {
+        self.file_manager
+    }
+    pub fn get_spec_version(&self) -> Option<SpecVersion> {
+        self.spec_version
+    }
+    pub fn get_submenu(&self) -> Option<&String> {
+        self.submenu.as_ref()
+    }
+    pub fn get_custom_keys(&self) -> &[CustomKey] {
+        &self.custom_keys
+    }
+    pub fn get_categories(&self) -> &[String] {
+        &self.categories
+    }
+    pub fn get_categories_prefilled(&self) -> bool {
+        self.categories_prefilled
+    }
+    pub fn get_only_shown_in(&self) -> &[String] {
+        &self.only_shown_in
+    }
+    pub fn get_not_shown_in(&self) -> &[String] {
+        &self.not_shown_in
+    }
+    pub fn get_current_desktop_only(&self) -> bool {
+        self.current_desktop_only
+    }
+    pub fn get_url(&self) -> &str {
+        &self.url
+    }
+    pub fn get_entry_type(&self) -> &EntryType {
+        &self.entry_type
+    }
 
     /// Asks user to overwrite file if the file is already exists.
-    fn ask_user_overwrite(&self) -> bool {
-        let mut file_name = self.name.clone();
-        file_name.push_str(".desktop");
+    /// Returns `false` if the input is closed without an answer.
+    fn ask_user_overwrite(&self) -> Result<bool, Error> {
+        let file_name = self.file_name();
 
         println!(
             "\"{}\" already exists in {:#?}. Do you want to replace it?[Y/N]: ",
@@ -292,16 +680,14 @@ impl Shortcut {
 
         loop {
             match std::io::stdin().read_line(&mut user_input) {
-                Err(e) => {
-                    println!("mkdsk: {e}");
-                    exit(16)
-                }
+                Err(e) => return Err(Error::new(16, e.to_string())),
+                Ok(0) => return Ok(false),
                 Ok(_) => (),
             };
 
             match user_input.to_lowercase().as_str().trim() {
-                "y" => return true,
-                "n" => return false,
+                "y" => return Ok(true),
+                "n" => return Ok(false),
                 _ => {
                     user_input.clear();
                     continue;
@@ -311,6 +697,74 @@ impl Shortcut {
     }
 }
 
+/// Returns value of the key that is a list of strings. Backslashes and semicolons of the items are escaped, the list
+/// always ends with a semicolon.
+pub fn list_value(items: &[String]) -> String {
+    items
+        .iter()
+        .map(|i| format!("{};", i.replace('\\', "\\\\").replace(';', "\\;")))
+        .collect()
+}
+
+/// Appends `items` that are not in the `list` yet.
+fn add_items(list: &mut Vec<String>, items: Vec<String>) {
+    for i in items {
+        if !list.contains(&i) {
+            list.push(i);
+        }
+    }
+}
+
+/// Returns `name` with characters that can not be used in the desktop file and icon ids replaced by `_`.
+pub fn file_id(name: &str) -> String {
+    name.chars()
+        .map(
+            |i| match i.is_ascii_alphanumeric() || i == '-' || i == '.' || i == '_' {
+                true => i,
+                false => '_',
+            },
+        )
+        .collect()
+}
+
+/// Quotes program path of the `Exec` key if it contains reserved characters.
+/// `%` is escaped since it starts the field codes.
+pub fn quote_exec(exec: &str) -> String {
+    const RESERVED: &str = " \t\n\"'\\><~|&;$*?#()`";
+
+    let exec = exec.replace('%', "%%");
+    if !exec.chars().any(|i| RESERVED.contains(i)) {
+        return exec;
+    }
+
+    let mut quoted = String::from("\"");
+    for i in exec.chars() {
+        // Backslash is escaped twice: once for the quoting and once for the key value
+        match i {
+            '"' | '`' | '$' => quoted.push_str("\\\\"),
+            '\\' => quoted.push_str("\\\\\\"),
+            _ => (),
+        }
+        quoted.push(i);
+    }
+    quoted.push('"');
+
+    quoted
+}
+
+/// Returns `file` URL of the absolute `path`.
+pub fn file_url(path: &Path) -> String {
+    let mut url = String::from("file://");
+    for i in path.to_string_lossy().bytes() {
+        match i.is_ascii_alphanumeric() || b"/-._~".contains(&i) {
+            true => url.push(i as char),
+            false => url.push_str(&format!("%{i:02X}")),
+        }
+    }
+
+    url
+}
+
 #[derive(PartialEq)]
 pub enum EntryType {
     Application,
@@ -327,3 +781,202 @@ impl EntryType {
         }
     }
 }
+
+/// Format of the created file.
+#[derive(PartialEq, Clone, Copy)]
+pub enum Format {
+    Desktop,
+    Url,
+    Webloc,
+    Lnk,
+}
+
+impl Format {
+    fn get_ext(&self) -> &str {
+        match self {
+            Format::Desktop => "desktop",
+            Format::Url => "url",
+            Format::Webloc => "webloc",
+            Format::Lnk => "lnk",
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "desktop" => Ok(Format::Desktop),
+            "url" => Ok(Format::Url),
+            "webloc" => Ok(Format::Webloc),
+            "lnk" => Ok(Format::Lnk),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Version of the Desktop Entry Specification the entry conforms to.
+#[derive(PartialEq, PartialOrd, Clone, Copy)]
+pub enum SpecVersion {
+    V1_0,
+    V1_1,
+    V1_4,
+    V1_5,
+}
+
+impl SpecVersion {
+    pub fn get_str(&self) -> &str {
+        match self {
+            SpecVersion::V1_0 => "1.0",
+            SpecVersion::V1_1 => "1.1",
+            SpecVersion::V1_4 => "1.4",
+            SpecVersion::V1_5 => "1.5",
+        }
+    }
+}
+
+impl FromStr for SpecVersion {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1.0" => Ok(SpecVersion::V1_0),
+            "1.1" => Ok(SpecVersion::V1_1),
+            "1.4" => Ok(SpecVersion::V1_4),
+            "1.5" => Ok(SpecVersion::V1_5),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Keys introduced after the first version of the specification.
+const KEY_VERSIONS: [(&str, SpecVersion); 6] = [
+    ("DBusActivatable", SpecVersion::V1_1),
+    ("Actions", SpecVersion::V1_1),
+    ("Implements", SpecVersion::V1_1),
+    ("Keywords", SpecVersion::V1_1),
+    ("PrefersNonDefaultGPU", SpecVersion::V1_4),
+    ("SingleMainWindow", SpecVersion::V1_5),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn application() -> Shortcut {
+        let mut shortcut = Shortcut::new();
+        shortcut.set_entry_type(EntryType::Application);
+        shortcut.set_out(PathBuf::from("/tmp"));
+        shortcut.set_name(String::from("App"));
+        shortcut.set_exec(PathBuf::from("/opt/app/app"));
+        shortcut
+    }
+
+    fn link(url: &str) -> Shortcut {
+        let mut shortcut = Shortcut::new();
+        shortcut.set_entry_type(EntryType::Link);
+        shortcut.set_url(url.to_string());
+        shortcut
+    }
+
+    #[test]
+    fn spec_version_keys() {
+        let mut shortcut = application();
+        shortcut.add_actions(vec![String::from("new")]);
+        shortcut.add_keywords(vec![String::from("editor")]);
+        shortcut.set_d_bus_activatable(true);
+        shortcut.set_prefers_non_default_gpu(true);
+        shortcut.set_single_main_window(true);
+
+        let text = shortcut.to_desktop_entry();
+        assert!(!text.contains("Version="));
+        assert!(text.contains("Actions=new;\n"));
+        assert!(text.contains("SingleMainWindow=true\n"));
+
+        shortcut.set_spec_version(SpecVersion::V1_0);
+        let text = shortcut.to_desktop_entry();
+        assert!(text.contains("Version=1.0\n"));
+        for key in [
+            "Actions=",
+            "Keywords=",
+            "DBusActivatable=",
+            "PrefersNonDefaultGPU=",
+        ] {
+            assert!(!text.contains(key), "{key} is written for 1.0");
+        }
+
+        shortcut.set_spec_version(SpecVersion::V1_4);
+        let text = shortcut.to_desktop_entry();
+        assert!(text.contains("Version=1.4\n"));
+        assert!(text.contains("Keywords=editor;\n"));
+        assert!(text.contains("PrefersNonDefaultGPU=true\n"));
+        assert!(!text.contains("SingleMainWindow="));
+        let unsupported: Vec<&str> = shortcut.unsupported_keys().iter().map(|i| i.0).collect();
+        assert_eq!(unsupported, ["SingleMainWindow"]);
+
+        // Action group adds its id to the Actions key and is written only with it
+        let mut shortcut = application();
+        shortcut.add_custom_key(CustomKey::parse("[Desktop Action new]Name=New").unwrap());
+        let text = shortcut.to_desktop_entry();
+        assert!(text.contains("Actions=new;\n"));
+        assert!(text.contains("\n[Desktop Action new]\nName=New\n"));
+        shortcut.set_spec_version(SpecVersion::V1_0);
+        assert!(!shortcut.to_desktop_entry().contains("[Desktop Action new]"));
+    }
+
+    #[test]
+    fn boolean_keys() {
+        let mut shortcut = application();
+        let text = shortcut.to_desktop_entry();
+        for key in ["NoDisplay=", "Hidden=", "Terminal=", "StartupNotify="] {
+            assert!(!text.contains(key), "unset {key} is written");
+        }
+
+        shortcut.set_terminal(false);
+        shortcut.set_no_display(true);
+        let text = shortcut.to_desktop_entry();
+        assert!(text.contains("Terminal=false\n"));
+        assert!(text.contains("NoDisplay=true\n"));
+        assert!(!text.contains("Hidden="));
+
+        // Unset keys are written as false like before they became optional
+        shortcut.set_explicit_defaults(true);
+        let text = shortcut.to_desktop_entry();
+        for key in [
+            "NoDisplay=true",
+            "Hidden=false",
+            "DBusActivatable=false",
+            "Terminal=false",
+            "StartupNotify=false",
+            "PrefersNonDefaultGPU=false",
+            "SingleMainWindow=false",
+        ] {
+            assert!(text.contains(&format!("{key}\n")), "{key} is not written");
+        }
+    }
+
+    #[test]
+    fn link_formats() {
+        let shortcut = link("https://example.com/?a=1&b=<2>");
+        assert_eq!(
+            shortcut.to_url_file().unwrap(),
+            "[InternetShortcut]\r\nURL=https://example.com/?a=1&b=<2>\r\n"
+        );
+        assert!(shortcut
+            .to_webloc()
+            .unwrap()
+            .contains("<string>https://example.com/?a=1&amp;b=&lt;2&gt;</string>"));
+
+        let mut shortcut = Shortcut::new();
+        shortcut.set_entry_type(EntryType::Application);
+        shortcut.set_exec(PathBuf::from("sh"));
+        assert!(shortcut.to_url_file().is_err());
+        assert!(shortcut.to_webloc().is_err());
+
+        // Programs found in PATH are written as absolute paths
+        let lnk = Lnk::parse(&shortcut.to_lnk().unwrap()).unwrap();
+        let target = to_windows_path(&which::which("sh").unwrap());
+        assert_eq!(lnk.target, Some(target));
+    }
+}
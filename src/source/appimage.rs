@@ -0,0 +1,195 @@
+//! # appimage
+//! Reads `.desktop` file and icon embedded into AppImages without executing them.
+//!
+//! See: https://github.com/AppImage/AppImageSpec/blob/master/draft.md
+
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use super::{apply_desktop_metadata, iso9660::Iso9660, squashfs::SquashFs};
+use crate::keyfile::KeyFile;
+use crate::shortcut::Shortcut;
+
+const ELF_MAGIC: &[u8; 4] = b"\x7fELF";
+/// AppImage magic bytes placed at offset 8 of the ELF header, followed by the AppImage type.
+const APPIMAGE_MAGIC: &[u8; 2] = b"AI";
+/// Offset of the ISO 9660 volume descriptor identifier.
+const ISO9660_MAGIC_OFFSET: u64 = 32769;
+/// Name of the AppImage icon file in the image root.
+const DIR_ICON: &str = ".DirIcon";
+
+/// Filesystem embedded into the AppImage.
+enum Image {
+    Iso9660(Iso9660),
+    SquashFs(SquashFs),
+}
+
+impl Image {
+    fn list_root(&mut self) -> Result<Vec<String>, String> {
+        match self {
+            Image::Iso9660(i) => i.list_root(),
+            Image::SquashFs(i) => i.list_root(),
+        }
+    }
+    fn read_file(&mut self, path: &str) -> Result<Vec<u8>, String> {
+        match self {
+            Image::Iso9660(i) => i.read_file(path),
+            Image::SquashFs(i) => i.read_file(path),
+        }
+    }
+}
+
+/// Metadata embedded into the AppImage.
+pub struct AppImage {
+    pub desktop: Option<KeyFile>,
+    pub icon: Option<Vec<u8>>,
+}
+
+impl AppImage {
+    /// Reads AppImage metadata. Returns `Ok(None)` if the file is not an AppImage.
+    pub fn read(path: &Path) -> Result<Option<AppImage>, String> {
+        let mut image = match open_image(path)? {
+            Some(val) => val,
+            None => return Ok(None),
+        };
+
+        let root = image.list_root()?;
+
+        let desktop = match root.iter().find(|i| i.ends_with(".desktop")) {
+            Some(val) => Some(KeyFile::parse(&String::from_utf8_lossy(
+                &image.read_file(val)?,
+            ))),
+            None => None,
+        };
+
+        // Use .DirIcon, fall back to the icon named after the Icon key
+        let mut icon = image.read_file(DIR_ICON).ok();
+        if icon.is_none() {
+            if let Some(name) = desktop
+                .as_ref()
+                .and_then(|i| i.get("Desktop Entry", "Icon"))
+            {
+                icon = ["png", "svg", "xpm"]
+                    .into_iter()
+                    .map(|ext| format!("{name}.{ext}"))
+                    .filter(|i| root.contains(i))
+                    .find_map(|i| image.read_file(&i).ok());
+            }
+        }
+
+        Ok(Some(AppImage { desktop, icon }))
+    }
+
+    /// Fills `Shortcut` with the embedded metadata. Icon is installed later if no other icon was set.
+    pub fn apply(&self, shortcut: &mut Shortcut) {
+        if let Some(desktop) = &self.desktop {
+            apply_desktop_metadata(desktop, shortcut);
+
+            // Keep arguments of the embedded Exec, the program itself is the AppImage
+            if let Some((_, args)) = desktop
+                .get("Desktop Entry", "Exec")
+                .and_then(|i| i.split_once(' '))
+            {
+                shortcut.set_args(args.trim().to_string());
+            }
+        }
+
+        if let Some(icon) = &self.icon {
+            shortcut.set_embedded_icon(icon.clone());
+        }
+    }
+}
+
+/// Opens filesystem embedded into the AppImage. Returns `Ok(None)` if the file is not an AppImage.
+fn open_image(path: &Path) -> Result<Option<Image>, String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+
+    let mut header = [0u8; 64];
+    if file.read_exact(&mut header).is_err() || &header[0..4] != ELF_MAGIC {
+        return Ok(None);
+    }
+
+    let appimage_type = match &header[8..10] == APPIMAGE_MAGIC {
+        true => header[10],
+        // Early type 1 AppImages do not have magic bytes, check for the ISO 9660 image instead
+        false => {
+            let mut magic = [0u8; 5];
+            let is_iso = file
+                .seek(SeekFrom::Start(ISO9660_MAGIC_OFFSET))
+                .and_then(|_| file.read_exact(&mut magic))
+                .is_ok()
+                && &magic == b"CD001";
+
+            match is_iso {
+                true => 1,
+                false => return Ok(None),
+            }
+        }
+    };
+
+    match appimage_type {
+        1 => Ok(Some(Image::Iso9660(Iso9660::open(path)?))),
+        2 => Ok(Some(Image::SquashFs(SquashFs::open(
+            path,
+            elf_size(&header)?,
+        )?))),
+        _ => Err(format!("unsupported AppImage type {appimage_type}")),
+    }
+}
+
+/// Returns size of the ELF runtime. The SquashFS image of type 2 AppImages is appended right after it.
+/// ELF ends with the section header table.
+fn elf_size(header: &[u8; 64]) -> Result<u64, String> {
+    let is_64 = header[4] == 2;
+    let is_le = header[5] == 1;
+
+    let read = |pos: usize, len: usize| -> u64 {
+        let mut bytes = [0u8; 8];
+        bytes[..len].copy_from_slice(&header[pos..pos + len]);
+        if !is_le {
+            bytes[..len].reverse();
+        }
+        u64::from_le_bytes(bytes)
+    };
+
+    let (shoff, shentsize, shnum) = match is_64 {
+        true => (read(0x28, 8), read(0x3A, 2), read(0x3C, 2)),
+        false => (read(0x20, 4), read(0x2E, 2), read(0x30, 2)),
+    };
+
+    shentsize
+        .checked_mul(shnum)
+        .and_then(|i| i.checked_add(shoff))
+        .ok_or(String::from("invalid ELF section header table"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runtime_size() {
+        let mut header = [0u8; 64];
+        header[4] = 2;
+        header[5] = 1;
+        header[0x28..0x30].copy_from_slice(&0x1000u64.to_le_bytes());
+        header[0x3A..0x3C].copy_from_slice(&64u16.to_le_bytes());
+        header[0x3C..0x3E].copy_from_slice(&3u16.to_le_bytes());
+        assert_eq!(elf_size(&header), Ok(0x1000 + 64 * 3));
+
+        header[0x28..0x30].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(elf_size(&header).is_err());
+
+        // 32-bit big-endian runtime
+        let mut header = [0u8; 64];
+        header[4] = 1;
+        header[5] = 2;
+        header[0x20..0x24].copy_from_slice(&0x2000u32.to_be_bytes());
+        header[0x2E..0x30].copy_from_slice(&40u16.to_be_bytes());
+        header[0x30..0x32].copy_from_slice(&2u16.to_be_bytes());
+        assert_eq!(elf_size(&header), Ok(0x2000 + 40 * 2));
+    }
+}
@@ -0,0 +1,171 @@
+//! # flatpak
+//! Reads metadata of the installed Flatpak applications from the installation exports.
+
+use std::{fs, path::PathBuf};
+
+use super::apply_desktop_metadata;
+use crate::icon::lookup_icon;
+use crate::keyfile::KeyFile;
+use crate::settings::DEFAULT_ICON_SIZE;
+use crate::shortcut::{EntryType, Shortcut};
+use crate::xdg::data_home;
+
+/// System wide Flatpak installation.
+const SYSTEM_INSTALLATION: &str = "/var/lib/flatpak";
+
+/// Installed Flatpak application.
+pub struct Flatpak {
+    id: String,
+    /// Installation directory, e.g. `/var/lib/flatpak`.
+    installation: PathBuf,
+    desktop: Option<KeyFile>,
+}
+
+impl Flatpak {
+    /// Looks for the application with the given id in the user and system installations.
+    /// Returns `None` if `id` is not an installed Flatpak application.
+    pub fn find(id: &str) -> Option<Flatpak> {
+        // Application ids have at least 3 components, e.g. org.mozilla.firefox
+        let is_id = id.split('.').count() >= 3
+            && id.split('.').all(|i| {
+                !i.is_empty()
+                    && i.chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            });
+        if !is_id {
+            return None;
+        }
+
+        let installation = [
+            data_home().join("flatpak"),
+            PathBuf::from(SYSTEM_INSTALLATION),
+        ]
+        .into_iter()
+        .find(|i| i.join("app").join(id).join("current").exists())?;
+
+        let desktop = KeyFile::load(
+            &installation
+                .join("exports/share/applications")
+                .join(format!("{id}.desktop")),
+        );
+
+        Some(Flatpak {
+            id: id.to_string(),
+            installation,
+            desktop,
+        })
+    }
+
+    /// Fills `Shortcut` with the exported metadata. Entry runs the application with `flatpak run`.
+    pub fn apply(&self, shortcut: &mut Shortcut) {
+        shortcut.set_entry_type(EntryType::Application);
+        shortcut.set_exec(PathBuf::from("flatpak"));
+        shortcut.set_name(self.id.clone());
+
+        let mut args = format!("run {}", self.id);
+
+        if let Some(desktop) = &self.desktop {
+            apply_desktop_metadata(desktop, shortcut);
+
+            // Pass files and urls to the sandbox the same way the exported entry does
+            let exec = desktop.get("Desktop Entry", "Exec").unwrap_or_default();
+            let field_code = ["%U", "%u", "%F", "%f"]
+                .into_iter()
+                .find(|i| exec.split_whitespace().any(|arg| arg == *i));
+            if let Some(code) = field_code {
+                let forwarding = match code {
+                    "%U" | "%u" => "@@u",
+                    _ => "@@",
+                };
+                args = format!("run --file-forwarding {} {forwarding} {code} @@", self.id);
+            }
+
+            if let Some(wm_class) = desktop.get("Desktop Entry", "StartupWMClass") {
+                shortcut.set_startup_wm_class(wm_class.to_string());
+            }
+            if let Some(icon) = desktop.get("Desktop Entry", "Icon") {
+                if let Some(icon) = self.icon(icon) {
+                    shortcut.set_icon(icon);
+                }
+            }
+        }
+
+        shortcut.set_args(args);
+    }
+
+    /// Returns `Icon` key value. Icon name is used if the icon theme can find it,
+    /// otherwise the path to the best exported icon is used.
+    fn icon(&self, name: &str) -> Option<String> {
+        if lookup_icon(name, DEFAULT_ICON_SIZE, 1).is_some() {
+            return Some(name.to_string());
+        }
+
+        let hicolor = self.installation.join("exports/share/icons/hicolor");
+        let mut best: Option<(u32, PathBuf)> = None;
+
+        for size in fs::read_dir(&hicolor).ok()?.flatten() {
+            let dir_name = size.file_name().to_string_lossy().to_string();
+            let score = match dir_name.as_str() {
+                "scalable" => u32::MAX,
+                _ => dir_name
+                    .split('x')
+                    .next()
+                    .and_then(|i| i.parse::<u32>().ok())
+                    .unwrap_or_default(),
+            };
+
+            for ext in ["svg", "png"] {
+                let file = size.path().join("apps").join(format!("{name}.{ext}"));
+                if file.is_file() && best.as_ref().is_none_or(|(i, _)| *i < score) {
+                    best = Some((score, file));
+                }
+            }
+        }
+
+        best.and_then(|(_, file)| file.to_str().map(String::from))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_forwarding() {
+        let exec = |desktop_exec: Option<&str>| {
+            let flatpak = Flatpak {
+                id: String::from("org.example.App"),
+                installation: PathBuf::from("/nonexistent"),
+                desktop: desktop_exec
+                    .map(|i| KeyFile::parse(&format!("[Desktop Entry]\nName=App\nExec={i}\n"))),
+            };
+            let mut shortcut = Shortcut::new();
+            shortcut.set_out(PathBuf::from("/tmp"));
+            flatpak.apply(&mut shortcut);
+
+            shortcut
+                .to_desktop_entry()
+                .lines()
+                .find_map(|i| i.strip_prefix("Exec=").map(String::from))
+                .unwrap()
+        };
+
+        assert_eq!(exec(None), "flatpak run org.example.App");
+        assert_eq!(
+            exec(Some("/usr/bin/flatpak run org.example.App")),
+            "flatpak run org.example.App"
+        );
+        assert_eq!(
+            exec(Some(
+                "/usr/bin/flatpak run --file-forwarding org.example.App @@u %U @@"
+            )),
+            "flatpak run --file-forwarding org.example.App @@u %U @@"
+        );
+        assert_eq!(
+            exec(Some(
+                "/usr/bin/flatpak run --file-forwarding org.example.App @@ %f @@"
+            )),
+            "flatpak run --file-forwarding org.example.App @@ %f @@"
+        );
+    }
+}
@@ -0,0 +1,365 @@
+//! # iso9660
+//! Read-only access to files stored in an ISO 9660 image with Rock Ridge extensions. Used to read type 1 AppImages.
+
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use super::resolve_link;
+
+/// Logical block size. ISO 9660 allows other sizes, but images written to CDs and by the common tools use 2048.
+const BLOCK_SIZE: u32 = 2048;
+/// Offset of the primary volume descriptor.
+const PVD_OFFSET: u64 = 16 * BLOCK_SIZE as u64;
+/// Maximum number of symlinks followed while resolving a path.
+const MAX_SYMLINKS: usize = 16;
+/// Maximum number of Rock Ridge continuation areas read for a single record.
+const MAX_CONTINUATIONS: usize = 16;
+
+const FLAG_DIRECTORY: u8 = 0x02;
+/// Rock Ridge `NM` and `SL` component flags.
+const RR_CONTINUE: u8 = 0x01;
+const RR_CURRENT: u8 = 0x02;
+const RR_PARENT: u8 = 0x04;
+const RR_ROOT: u8 = 0x08;
+
+/// Directory record.
+#[derive(Clone)]
+struct Record {
+    name: String,
+    extent: u32,
+    size: u32,
+    is_dir: bool,
+    symlink: Option<String>,
+}
+
+/// Rock Ridge entries of the directory record.
+#[derive(Default)]
+struct RockRidge {
+    name: String,
+    link: String,
+    /// Block, offset and length of the continuation area that contains the next entries.
+    continuation: Option<(u32, u32, u32)>,
+}
+
+/// ISO 9660 image opened for reading.
+pub struct Iso9660 {
+    file: File,
+    root: Record,
+}
+
+impl Iso9660 {
+    pub fn open(path: &Path) -> Result<Iso9660, String> {
+        let mut file = File::open(path).map_err(|e| e.to_string())?;
+
+        let mut pvd = [0u8; BLOCK_SIZE as usize];
+        file.seek(SeekFrom::Start(PVD_OFFSET))
+            .and_then(|_| file.read_exact(&mut pvd))
+            .map_err(|e| e.to_string())?;
+
+        if pvd[0] != 1 || &pvd[1..6] != b"CD001" {
+            return Err(String::from("not an ISO 9660 image"));
+        }
+
+        let block_size = u16::from_le_bytes([pvd[128], pvd[129]]) as u32;
+        if block_size != BLOCK_SIZE {
+            return Err(format!("unsupported ISO 9660 block size {block_size}"));
+        }
+
+        let (root, _) = parse_record(&pvd[156..190]).ok_or("invalid ISO 9660 root directory")?;
+
+        Ok(Iso9660 { file, root })
+    }
+
+    /// Returns names of the files in the root directory.
+    pub fn list_root(&mut self) -> Result<Vec<String>, String> {
+        let root = self.root.clone();
+
+        Ok(self.read_dir(&root)?.into_iter().map(|i| i.name).collect())
+    }
+
+    /// Reads file content. Symlinks are followed, `path` is relative to the image root.
+    pub fn read_file(&mut self, path: &str) -> Result<Vec<u8>, String> {
+        let mut path = path.to_string();
+
+        for _ in 0..MAX_SYMLINKS {
+            let record = self.lookup(&path)?;
+
+            if let Some(target) = record.symlink {
+                path = resolve_link(&path, &target);
+                continue;
+            }
+            if record.is_dir {
+                return Err(format!("{path} is a directory"));
+            }
+
+            return self.read_extent(record.extent, record.size);
+        }
+
+        Err(format!("{path}: too many levels of symbolic links"))
+    }
+
+    /// Returns record of the `path` without following the last symlink.
+    fn lookup(&mut self, path: &str) -> Result<Record, String> {
+        let mut record = self.root.clone();
+
+        for name in path.split('/').filter(|i| !i.is_empty() && *i != ".") {
+            record = self
+                .read_dir(&record)?
+                .into_iter()
+                .find(|i| i.name == name)
+                .ok_or(format!("{path}: no such file"))?;
+        }
+
+        Ok(record)
+    }
+
+    fn read_dir(&mut self, dir: &Record) -> Result<Vec<Record>, String> {
+        if !dir.is_dir {
+            return Err(format!("{} is not a directory", dir.name));
+        }
+
+        let data = self.read_extent(dir.extent, dir.size)?;
+
+        let mut records = Vec::new();
+        let mut pos = 0;
+        while pos < data.len() {
+            let len = data[pos] as usize;
+
+            // Records do not cross block boundaries, zero length means that the rest of the block is padding
+            if len == 0 {
+                pos = (pos / BLOCK_SIZE as usize + 1) * BLOCK_SIZE as usize;
+                continue;
+            }
+
+            if let Some((mut record, mut rock_ridge)) =
+                data.get(pos..pos + len).and_then(parse_record)
+            {
+                // Long names and symlinks may not fit into the record and are continued in other blocks
+                for _ in 0..MAX_CONTINUATIONS {
+                    let Some((block, offset, len)) = rock_ridge.continuation.take() else {
+                        break;
+                    };
+                    let area =
+                        self.read_at(block as u64 * BLOCK_SIZE as u64 + offset as u64, len)?;
+                    rock_ridge.parse(&area);
+                }
+                rock_ridge.apply(&mut record);
+
+                if record.name != "." && record.name != ".." {
+                    records.push(record);
+                }
+            }
+            pos += len;
+        }
+
+        Ok(records)
+    }
+
+    fn read_extent(&mut self, extent: u32, size: u32) -> Result<Vec<u8>, String> {
+        self.read_at(extent as u64 * BLOCK_SIZE as u64, size)
+    }
+
+    fn read_at(&mut self, pos: u64, size: u32) -> Result<Vec<u8>, String> {
+        let mut data = vec![0u8; size as usize];
+
+        self.file
+            .seek(SeekFrom::Start(pos))
+            .and_then(|_| self.file.read_exact(&mut data))
+            .map_err(|e| e.to_string())?;
+
+        Ok(data)
+    }
+}
+
+/// Parses directory record. Rock Ridge entries of the system use area are returned separately since they can be
+/// continued in the other blocks.
+fn parse_record(data: &[u8]) -> Option<(Record, RockRidge)> {
+    if data.len() < 34 {
+        return None;
+    }
+
+    let name_len = data[32] as usize;
+    let raw_name = data.get(33..33 + name_len)?;
+
+    let name = match raw_name {
+        [0] => String::from("."),
+        [1] => String::from(".."),
+        _ => {
+            // Strip version and trailing dot: "FILE.TXT;1" -> "file.txt"
+            let name = String::from_utf8_lossy(raw_name);
+            let name = name.split(';').next().unwrap_or_default();
+            name.trim_end_matches('.').to_lowercase()
+        }
+    };
+
+    // System use area follows the name and a padding byte if the name length is even
+    let mut rock_ridge = RockRidge::default();
+    if let Some(val) = data.get(33 + name_len + (name_len + 1) % 2..) {
+        rock_ridge.parse(val);
+    }
+
+    let record = Record {
+        name,
+        extent: u32::from_le_bytes(data[2..6].try_into().unwrap()),
+        size: u32::from_le_bytes(data[10..14].try_into().unwrap()),
+        is_dir: data[25] & FLAG_DIRECTORY != 0,
+        symlink: None,
+    };
+
+    Some((record, rock_ridge))
+}
+
+impl RockRidge {
+    /// Parses system use entries. Names and symlinks are appended to the already parsed ones.
+    fn parse(&mut self, data: &[u8]) {
+        let mut pos = 0;
+        while pos + 4 <= data.len() {
+            let signature = &data[pos..pos + 2];
+            let len = data[pos + 2] as usize;
+            if len < 4 || pos + len > data.len() {
+                break;
+            }
+            let entry = &data[pos + 4..pos + len];
+
+            match signature {
+                b"NM" if !entry.is_empty() => {
+                    self.name.push_str(&String::from_utf8_lossy(&entry[1..]));
+                }
+                b"SL" if !entry.is_empty() => self.parse_link(&entry[1..]),
+                b"CE" if entry.len() >= 20 => {
+                    self.continuation = Some((
+                        u32::from_le_bytes(entry[0..4].try_into().unwrap()),
+                        u32::from_le_bytes(entry[8..12].try_into().unwrap()),
+                        u32::from_le_bytes(entry[16..20].try_into().unwrap()),
+                    ));
+                }
+                b"ST" => break,
+                _ => (),
+            }
+
+            pos += len;
+        }
+    }
+
+    /// Parses component records of the `SL` entry.
+    fn parse_link(&mut self, data: &[u8]) {
+        let mut component = 0;
+        while component + 2 <= data.len() {
+            let flags = data[component];
+            let component_len = data[component + 1] as usize;
+            let Some(content) = data.get(component + 2..component + 2 + component_len) else {
+                return;
+            };
+
+            if flags & RR_ROOT != 0 {
+                self.link.push('/');
+            } else {
+                if !self.link.is_empty() && !self.link.ends_with('/') {
+                    self.link.push('/');
+                }
+                match flags {
+                    i if i & RR_CURRENT != 0 => self.link.push('.'),
+                    i if i & RR_PARENT != 0 => self.link.push_str(".."),
+                    _ => self.link.push_str(&String::from_utf8_lossy(content)),
+                }
+            }
+            // Continued components are glued to the next one
+            if flags & RR_CONTINUE != 0 {
+                self.link.push('\0');
+            }
+
+            component += 2 + component_len;
+        }
+    }
+
+    /// Replaces name of the `record` and sets its symlink target.
+    fn apply(self, record: &mut Record) {
+        if !self.name.is_empty() {
+            record.name = self.name;
+        }
+        if !self.link.is_empty() {
+            record.symlink = Some(self.link.replace("\0/", ""));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(name: &[u8], extent: u32, size: u32, flags: u8, system_use: &[u8]) -> Vec<u8> {
+        let mut data = vec![0u8; 33];
+        data[2..6].copy_from_slice(&extent.to_le_bytes());
+        data[10..14].copy_from_slice(&size.to_le_bytes());
+        data[25] = flags;
+        data[32] = name.len() as u8;
+        data.extend_from_slice(name);
+        data.resize(data.len() + (name.len() + 1) % 2, 0);
+        data.extend_from_slice(system_use);
+        data[0] = data.len() as u8;
+        data
+    }
+
+    fn entry(signature: &[u8], content: &[u8]) -> Vec<u8> {
+        let mut data = signature.to_vec();
+        data.extend([4 + content.len() as u8, 1]);
+        data.extend_from_slice(content);
+        data
+    }
+
+    fn image(block_size: u16) -> Vec<u8> {
+        let mut image = vec![0u8; 21 * BLOCK_SIZE as usize];
+        let block = |i: usize| i * BLOCK_SIZE as usize;
+
+        let pvd = &mut image[block(16)..block(17)];
+        pvd[..6].copy_from_slice(b"\x01CD001");
+        pvd[128..130].copy_from_slice(&block_size.to_le_bytes());
+        pvd[156..190].copy_from_slice(&record(&[0], 18, BLOCK_SIZE, FLAG_DIRECTORY, &[]));
+
+        // Name of the file is continued in the block 19
+        let continuation = entry(b"NM", b"\x00name.txt");
+        let mut ce = Vec::new();
+        for i in [19, 0, continuation.len() as u32] {
+            ce.extend(i.to_le_bytes());
+            ce.extend(i.to_be_bytes());
+        }
+        let file = [entry(b"NM", b"\x01long-"), entry(b"CE", &ce)].concat();
+        let link = [
+            entry(b"NM", b"\x00link"),
+            entry(b"SL", b"\x00\x00\x0dlong-name.txt"),
+        ]
+        .concat();
+
+        let dir = [
+            record(&[0], 18, BLOCK_SIZE, FLAG_DIRECTORY, &[]),
+            record(&[1], 18, BLOCK_SIZE, FLAG_DIRECTORY, &[]),
+            record(b"LONG_NAM.TXT;1", 20, 5, 0, &file),
+            record(b"LINK.;1", 0, 0, 0, &link),
+        ]
+        .concat();
+        image[block(18)..block(18) + dir.len()].copy_from_slice(&dir);
+        image[block(19)..block(19) + continuation.len()].copy_from_slice(&continuation);
+        image[block(20)..block(20) + 5].copy_from_slice(b"hello");
+
+        image
+    }
+
+    #[test]
+    fn iso9660_reading() {
+        let path = std::env::temp_dir().join(format!("mkdsk-test-{}.iso", std::process::id()));
+
+        std::fs::write(&path, image(2048)).unwrap();
+        let mut iso = Iso9660::open(&path).unwrap();
+        assert_eq!(iso.list_root().unwrap(), ["long-name.txt", "link"]);
+        assert_eq!(iso.read_file("long-name.txt").unwrap(), b"hello");
+        assert_eq!(iso.read_file("link").unwrap(), b"hello");
+
+        std::fs::write(&path, image(0)).unwrap();
+        assert!(Iso9660::open(&path).is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
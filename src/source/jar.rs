@@ -0,0 +1,102 @@
+//! # jar
+//! Reads the manifest of the Java `.jar` files.
+//!
+//! See: https://docs.oracle.com/en/java/javase/21/docs/specs/jar/jar.html#jar-manifest
+
+use std::path::Path;
+
+use super::zip::read_zip_file;
+use crate::shortcut::{EntryType, Shortcut};
+
+const MANIFEST: &str = "META-INF/MANIFEST.MF";
+/// Command the `.jar` files are run with, unless `--java` is used.
+pub const JAVA_COMMAND: &str = "java -jar";
+
+/// Java archive.
+#[derive(Default)]
+pub struct Jar {
+    /// Attributes of the manifest main section.
+    manifest: Vec<(String, String)>,
+}
+
+impl Jar {
+    /// Reads the jar manifest. Jars without the manifest have no attributes.
+    pub fn read(path: &Path) -> Result<Jar, String> {
+        let manifest = match read_zip_file(path, MANIFEST)? {
+            Some(val) => parse_manifest(&String::from_utf8_lossy(&val)),
+            None => Vec::new(),
+        };
+
+        Ok(Jar { manifest })
+    }
+
+    /// Returns value of the main section attribute. Attribute names are case-insensitive.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.manifest
+            .iter()
+            .find(|(i, _)| i.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+            .filter(|i| !i.is_empty())
+    }
+
+    /// Fills `Shortcut` with the manifest metadata. Entry runs the jar with `java -jar`.
+    pub fn apply(&self, shortcut: &mut Shortcut) {
+        shortcut.set_entry_type(EntryType::Application);
+        shortcut.set_interpreter(Some(JAVA_COMMAND.to_string()));
+        shortcut.set_args(String::from("%F"));
+
+        if let Some(title) = self.get("Implementation-Title") {
+            shortcut.set_name(title.to_string());
+        }
+        if let Some(version) = self.get("Implementation-Version") {
+            shortcut.set_comment(format!("{} {version}", shortcut.get_name()));
+        }
+
+        match self.get("Main-Class") {
+            // AWT names windows after the main class with dots replaced by dashes
+            Some(main_class) => shortcut.set_startup_wm_class(main_class.replace('.', "-")),
+            None => {
+                println!("mkdsk: warning: jar has no Main-Class, it can not be run with java -jar")
+            }
+        }
+    }
+}
+
+/// Parses main section of the manifest. Lines starting with a space continue the previous value.
+fn parse_manifest(text: &str) -> Vec<(String, String)> {
+    let mut attributes: Vec<(String, String)> = Vec::new();
+
+    for line in text.lines() {
+        if line.is_empty() {
+            break;
+        }
+
+        if let Some(continuation) = line.strip_prefix(' ') {
+            if let Some((_, value)) = attributes.last_mut() {
+                value.push_str(continuation);
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            attributes.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    attributes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manifest_parsing() {
+        let jar = Jar {
+            manifest: parse_manifest(
+                "Manifest-Version: 1.0\r\nMain-Class: org.example.gui.Ma\r\n inWindow\r\n\r\nName: org/example/\r\nMain-Class: Other\r\n",
+            ),
+        };
+
+        assert_eq!(jar.get("main-class"), Some("org.example.gui.MainWindow"));
+        assert_eq!(jar.get("Manifest-Version"), Some("1.0"));
+        assert_eq!(jar.get("Name"), None);
+    }
+}
@@ -0,0 +1,82 @@
+//! # source
+//! This module contains readers for the entry sources that carry their own metadata.
+
+pub mod appimage;
+pub mod flatpak;
+mod iso9660;
+pub mod jar;
+pub mod pe;
+pub mod script;
+pub mod snap;
+mod squashfs;
+mod zip;
+
+use crate::keyfile::KeyFile;
+use crate::shortcut::Shortcut;
+
+/// Fills `Shortcut` with the metadata of the `.desktop` file shipped with the source.
+fn apply_desktop_metadata(desktop: &KeyFile, shortcut: &mut Shortcut) {
+    let get = |key: &str| {
+        desktop
+            .get("Desktop Entry", key)
+            .filter(|i| !i.is_empty())
+            .map(String::from)
+    };
+
+    if let Some(val) = get("Name") {
+        shortcut.set_name(val);
+    }
+    if let Some(val) = get("Comment") {
+        shortcut.set_comment(val);
+    }
+    let get_list = |key: &str| {
+        desktop
+            .get_list("Desktop Entry", key)
+            .filter(|i| !i.is_empty())
+    };
+
+    if let Some(val) = get_list("Categories") {
+        shortcut.prefill_categories(val);
+    }
+    if let Some(val) = get_list("MimeType") {
+        shortcut.prefill_mime_type(val);
+    }
+}
+
+/// Resolves symlink `target` relative to the directory of the `path`. Both paths are relative to the image root.
+fn resolve_link(path: &str, target: &str) -> String {
+    let mut components: Vec<&str> = match target.starts_with('/') {
+        true => Vec::new(),
+        false => path.split('/').filter(|i| !i.is_empty()).collect(),
+    };
+    // Remove symlink name
+    components.pop();
+
+    for i in target.split('/') {
+        match i {
+            "" | "." => (),
+            ".." => {
+                components.pop();
+            }
+            _ => components.push(i),
+        }
+    }
+
+    components.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn symlink_resolution() {
+        assert_eq!(resolve_link(".DirIcon", "app.png"), "app.png");
+        assert_eq!(
+            resolve_link(".DirIcon", "usr/share/icons/app.png"),
+            "usr/share/icons/app.png"
+        );
+        assert_eq!(resolve_link("usr/bin/app", "../lib/./app"), "usr/lib/app");
+        assert_eq!(resolve_link("usr/bin/app", "/opt/app"), "opt/app");
+    }
+}
@@ -0,0 +1,499 @@
+//! # pe
+//! Reads icon and version information stored in the resource section of the Windows PE executables.
+//!
+//! See: https://learn.microsoft.com/en-us/windows/win32/debug/pe-format
+
+use std::{
+    fs::{self, File},
+    io::{Cursor, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use image::ImageFormat;
+
+use crate::bytes::{u16_at, u32_at, utf16_string};
+use crate::shortcut::{EntryType, Shortcut};
+
+const DOS_MAGIC: &[u8; 2] = b"MZ";
+const PE_MAGIC: &[u8; 4] = b"PE\0\0";
+/// Offset of the PE header offset in the DOS header.
+const PE_OFFSET_POS: usize = 0x3c;
+const COFF_HEADER_SIZE: usize = 20;
+const SECTION_HEADER_SIZE: usize = 40;
+const PNG_MAGIC: &[u8; 8] = b"\x89PNG\r\n\x1a\n";
+
+const OPTIONAL_MAGIC_PE32: u16 = 0x10b;
+const OPTIONAL_MAGIC_PE32_PLUS: u16 = 0x20b;
+/// Index of the resource table in the optional header data directories.
+const RESOURCE_DIRECTORY: usize = 2;
+
+const RT_ICON: u32 = 3;
+const RT_GROUP_ICON: u32 = 14;
+const RT_VERSION: u32 = 16;
+/// Bit of the resource directory entry offset that indicates a subdirectory.
+const SUBDIRECTORY: u32 = 1 << 31;
+
+/// Command Windows executables are run with, unless `--wine` is used.
+pub const WINE_COMMAND: &str = "wine";
+
+/// Windows PE executable.
+#[derive(Default)]
+pub struct Pe {
+    /// Largest icon converted to PNG.
+    pub icon: Option<Vec<u8>>,
+    /// Strings of the version information, e.g. `ProductName`.
+    version: Vec<(String, String)>,
+}
+
+/// Returns `true` if the file at `path` is a PE executable.
+pub fn is_pe(path: &Path) -> bool {
+    let mut file = match File::open(path) {
+        Ok(val) => val,
+        Err(_) => return false,
+    };
+
+    let mut header = [0u8; 0x40];
+    if file.read_exact(&mut header).is_err() || &header[0..2] != DOS_MAGIC {
+        return false;
+    }
+
+    let pe = u32_at(&header, PE_OFFSET_POS).unwrap() as u64;
+    let mut magic = [0u8; 4];
+    file.seek(SeekFrom::Start(pe))
+        .and_then(|_| file.read_exact(&mut magic))
+        .is_ok_and(|_| &magic == PE_MAGIC)
+}
+
+impl Pe {
+    /// Reads resources of the PE executable.
+    pub fn read(path: &Path) -> Result<Pe, String> {
+        let data = fs::read(path).map_err(|e| e.to_string())?;
+        let resources = match Resources::open(&data) {
+            Some(val) => val,
+            None => return Err(String::from("corrupted PE resource section")),
+        };
+
+        let version = resources
+            .find(RT_VERSION, None)
+            .map(read_version)
+            .unwrap_or_default();
+
+        // Version information is still useful if the icon is broken
+        let icon = resources
+            .largest_icon()
+            .and_then(|icon| match icon_to_png(&icon) {
+                Ok(val) => Some(val),
+                Err(e) => {
+                    println!("mkdsk: warning: {}: {e}", path.display());
+                    None
+                }
+            });
+
+        Ok(Pe { icon, version })
+    }
+
+    /// Returns string of the version information.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.version
+            .iter()
+            .find(|(i, _)| i == name)
+            .map(|(_, value)| value.trim())
+            .filter(|i| !i.is_empty())
+    }
+
+    /// Fills `Shortcut` with the version information and icon. Entry runs the executable with `wine`.
+    pub fn apply(&self, shortcut: &mut Shortcut) {
+        shortcut.set_entry_type(EntryType::Application);
+        shortcut.set_interpreter(Some(WINE_COMMAND.to_string()));
+
+        let description = self.get("FileDescription");
+        if let Some(name) = self.get("ProductName").or(description) {
+            shortcut.set_name(name.to_string());
+        }
+        if let Some(comment) = description.filter(|i| *i != shortcut.get_name()) {
+            shortcut.set_comment(comment.to_string());
+        }
+
+        // Wine names windows after the lowercase executable name
+        if let Some(exec) = shortcut.get_exec().and_then(|i| i.file_name()) {
+            let wm_class = exec.to_string_lossy().to_lowercase();
+            shortcut.set_startup_wm_class(wm_class);
+        }
+
+        if let Some(icon) = &self.icon {
+            shortcut.set_embedded_icon(icon.clone());
+        }
+    }
+}
+
+/// Resource section of the PE executable.
+struct Resources<'a> {
+    /// Content of the resource section.
+    section: &'a [u8],
+    /// RVA of the resource section.
+    rva: u32,
+}
+
+impl<'a> Resources<'a> {
+    /// Finds resource section of the PE executable. Executables without resources have an empty section.
+    fn open(data: &'a [u8]) -> Option<Resources<'a>> {
+        let pe = u32_at(data, PE_OFFSET_POS)? as usize;
+        let coff = pe + PE_MAGIC.len();
+        let sections = u16_at(data, coff + 2)? as usize;
+        let optional = coff + COFF_HEADER_SIZE;
+        let optional_size = u16_at(data, coff + 16)? as usize;
+
+        let directories = match u16_at(data, optional)? {
+            OPTIONAL_MAGIC_PE32 => optional + 96,
+            OPTIONAL_MAGIC_PE32_PLUS => optional + 112,
+            _ => return None,
+        };
+        let rva = u32_at(data, directories + RESOURCE_DIRECTORY * 8)?;
+        let size = u32_at(data, directories + RESOURCE_DIRECTORY * 8 + 4)?;
+        if rva == 0 || size == 0 {
+            return Some(Resources {
+                section: &[],
+                rva: 0,
+            });
+        }
+
+        // Find section that contains the resource table
+        let table = optional + optional_size;
+        for i in 0..sections {
+            let header = table + i * SECTION_HEADER_SIZE;
+            let address = u32_at(data, header + 12)?;
+            let raw_size = u32_at(data, header + 16)?;
+            let raw_pos = u32_at(data, header + 20)?;
+
+            if (address..address.saturating_add(raw_size)).contains(&rva) {
+                let start = raw_pos as usize + (rva - address) as usize;
+                let end = (raw_pos as usize + raw_size as usize).min(data.len());
+                return Some(Resources {
+                    section: data.get(start..end)?,
+                    rva,
+                });
+            }
+        }
+
+        None
+    }
+
+    /// Returns data of the resource with the given type and id. The first resource is returned if `id` is `None`.
+    fn find(&self, kind: u32, id: Option<u32>) -> Option<&'a [u8]> {
+        let names = self.entry(0, kind)?;
+        let languages = match id {
+            Some(id) => self.entry(names, id)?,
+            None => self.first_entry(names)?,
+        };
+        let data = self.first_entry(languages)?;
+
+        let rva = u32_at(self.section, data)?;
+        let size = u32_at(self.section, data + 4)? as usize;
+        let start = rva.checked_sub(self.rva)? as usize;
+
+        self.section.get(start..start + size)
+    }
+
+    /// Returns offset of the directory entry target with the given id.
+    fn entry(&self, dir: usize, id: u32) -> Option<usize> {
+        self.entries(dir)?
+            .into_iter()
+            .find(|(i, _)| *i == id)
+            .map(|(_, offset)| offset)
+    }
+
+    /// Returns offset of the first directory entry target.
+    fn first_entry(&self, dir: usize) -> Option<usize> {
+        self.entries(dir)?.first().map(|(_, offset)| *offset)
+    }
+
+    /// Returns ids and target offsets of the directory entries. Ids of the named entries are offsets of their names.
+    fn entries(&self, dir: usize) -> Option<Vec<(u32, usize)>> {
+        let named = u16_at(self.section, dir + 12)? as usize;
+        let ids = u16_at(self.section, dir + 14)? as usize;
+
+        (0..named + ids)
+            .map(|i| {
+                let entry = dir + 16 + i * 8;
+                let id = u32_at(self.section, entry)?;
+                let offset = u32_at(self.section, entry + 4)? & !SUBDIRECTORY;
+                Some((id, offset as usize))
+            })
+            .collect()
+    }
+
+    /// Returns the largest icon of the first icon group.
+    fn largest_icon(&self) -> Option<Vec<u8>> {
+        let group = self.find(RT_GROUP_ICON, None)?;
+        let count = u16_at(group, 4)? as usize;
+
+        // Group entries: width, height, colors, reserved, planes, bit count, size, id
+        let (_, id) = (0..count)
+            .filter_map(|i| {
+                let entry = group.get(6 + i * 14..6 + (i + 1) * 14)?;
+                let width = match entry[0] {
+                    0 => 256,
+                    val => val as u32,
+                };
+                Some(((width, u16_at(entry, 6)?), u16_at(entry, 12)?))
+            })
+            .max()?;
+
+        self.find(RT_ICON, Some(id as u32)).map(|i| i.to_vec())
+    }
+}
+
+/// Converts icon resource to PNG. Icons are stored either as PNG or as a bitmap without the file header.
+fn icon_to_png(icon: &[u8]) -> Result<Vec<u8>, String> {
+    if icon.starts_with(PNG_MAGIC) {
+        return Ok(icon.to_vec());
+    }
+
+    // Wrap bitmap into a single image .ico file, so it can be decoded. Size 256 and larger is written as 0
+    let size = |pos, divisor| match u32_at(icon, pos).map(|i| i / divisor) {
+        Some(val @ 1..=255) => val as u8,
+        _ => 0,
+    };
+    let mut ico = vec![0, 0, 1, 0, 1, 0];
+    // Bitmap height includes the transparency mask
+    ico.extend_from_slice(&[size(4, 1), size(8, 2), 0, 0, 1, 0]);
+    ico.extend_from_slice(&u16_at(icon, 14).unwrap_or(32).to_le_bytes());
+    ico.extend_from_slice(&(icon.len() as u32).to_le_bytes());
+    ico.extend_from_slice(&22u32.to_le_bytes());
+    ico.extend_from_slice(icon);
+
+    let image = image::load_from_memory_with_format(&ico, ImageFormat::Ico)
+        .map_err(|e| format!("can not decode icon: {e}"))?;
+
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .map_err(|e| e.to_string())?;
+
+    Ok(png)
+}
+
+/// Returns strings of the first string table of the `VS_VERSIONINFO` resource.
+fn read_version(data: &[u8]) -> Vec<(String, String)> {
+    let (_, _, children) = match read_block(data) {
+        Some(val) => val,
+        None => return Vec::new(),
+    };
+
+    let tables = read_blocks(children)
+        .into_iter()
+        .find(|(key, _, _)| key == "StringFileInfo")
+        .map(|(_, _, tables)| read_blocks(tables))
+        .unwrap_or_default();
+
+    match tables.first() {
+        Some((_, _, strings)) => read_blocks(strings)
+            .into_iter()
+            .map(|(key, value, _)| (key, utf16_string(value)))
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Returns key, value and children of the version information blocks stored one after another.
+fn read_blocks(mut data: &[u8]) -> Vec<(String, &[u8], &[u8])> {
+    let mut blocks = Vec::new();
+
+    while let Some(block) = read_block(data) {
+        blocks.push(block);
+
+        let len = align(u16_at(data, 0).unwrap() as usize);
+        data = data.get(len..).unwrap_or_default();
+    }
+
+    blocks
+}
+
+/// Returns key, value and children of the version information block at the start of `data`.
+fn read_block(data: &[u8]) -> Option<(String, &[u8], &[u8])> {
+    let len = u16_at(data, 0)? as usize;
+    let value_len = u16_at(data, 2)? as usize;
+    let is_text = u16_at(data, 4)? == 1;
+    let data = data.get(..len).filter(|_| len > 6)?;
+
+    let key = utf16_string(&data[6..]);
+    let value_pos = align(6 + (key.encode_utf16().count() + 1) * 2);
+    // Length of the text values is in characters
+    let value_end = value_pos
+        + match is_text {
+            true => value_len * 2,
+            false => value_len,
+        };
+
+    let value = data.get(value_pos..value_end.min(len))?;
+    let children = data.get(align(value_end).min(len)..)?;
+
+    Some((key, value, children))
+}
+
+/// Rounds `pos` up to the 32 bit boundary.
+fn align(pos: usize) -> usize {
+    pos.next_multiple_of(4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECTION_RVA: u32 = 0x1000;
+    const SECTION_POS: usize = 0x200;
+
+    /// Returns resource section with a single resource of each type.
+    fn resources(items: &[(u32, u32, Vec<u8>)]) -> Vec<u8> {
+        let dir = |id: u32, offset: usize| {
+            let mut data = vec![0u8; 14];
+            data.extend(1u16.to_le_bytes());
+            data.extend(id.to_le_bytes());
+            data.extend((offset as u32).to_le_bytes());
+            data
+        };
+
+        let tree_pos = 16 + 8 * items.len();
+        let mut data_pos = tree_pos + 64 * items.len();
+        let mut root = vec![0u8; 14];
+        root.extend((items.len() as u16).to_le_bytes());
+        let mut tree = Vec::new();
+        let mut blobs = Vec::new();
+
+        for (i, (kind, id, blob)) in items.iter().enumerate() {
+            let pos = tree_pos + i * 64;
+            root.extend(kind.to_le_bytes());
+            root.extend((pos as u32 | SUBDIRECTORY).to_le_bytes());
+
+            tree.extend(dir(*id, (pos + 24) | SUBDIRECTORY as usize));
+            tree.extend(dir(0x409, pos + 48));
+            tree.extend((SECTION_RVA + data_pos as u32).to_le_bytes());
+            tree.extend((blob.len() as u32).to_le_bytes());
+            tree.extend([0; 8]);
+
+            blobs.extend(blob);
+            blobs.resize(align(blobs.len()), 0);
+            data_pos = tree_pos + 64 * items.len() + blobs.len();
+        }
+
+        [root, tree, blobs].concat()
+    }
+
+    /// Returns PE32 executable with the resource `section`.
+    fn executable(section: &[u8]) -> Vec<u8> {
+        let mut data = vec![0u8; SECTION_POS];
+        data[..2].copy_from_slice(DOS_MAGIC);
+        data[PE_OFFSET_POS..PE_OFFSET_POS + 4].copy_from_slice(&0x40u32.to_le_bytes());
+        data[0x40..0x44].copy_from_slice(PE_MAGIC);
+
+        let coff = 0x44;
+        let optional = coff + COFF_HEADER_SIZE;
+        let optional_size = 96 + 16 * 8;
+        data[coff + 2..coff + 4].copy_from_slice(&1u16.to_le_bytes());
+        data[coff + 16..coff + 18].copy_from_slice(&(optional_size as u16).to_le_bytes());
+        data[optional..optional + 2].copy_from_slice(&OPTIONAL_MAGIC_PE32.to_le_bytes());
+
+        let directory = optional + 96 + RESOURCE_DIRECTORY * 8;
+        data[directory..directory + 4].copy_from_slice(&SECTION_RVA.to_le_bytes());
+        data[directory + 4..directory + 8].copy_from_slice(&(section.len() as u32).to_le_bytes());
+
+        let header = optional + optional_size;
+        data[header + 12..header + 16].copy_from_slice(&SECTION_RVA.to_le_bytes());
+        data[header + 16..header + 20].copy_from_slice(&(section.len() as u32).to_le_bytes());
+        data[header + 20..header + 24].copy_from_slice(&(SECTION_POS as u32).to_le_bytes());
+
+        data.extend_from_slice(section);
+        data
+    }
+
+    /// Returns version information block.
+    fn block(key: &str, text: Option<&str>, children: &[u8]) -> Vec<u8> {
+        let mut data = vec![0u8; 6];
+        data.extend(key.encode_utf16().chain([0]).flat_map(u16::to_le_bytes));
+        data.resize(align(data.len()), 0);
+
+        if let Some(text) = text {
+            let value: Vec<u16> = text.encode_utf16().chain([0]).collect();
+            data[2..4].copy_from_slice(&(value.len() as u16).to_le_bytes());
+            data[4..6].copy_from_slice(&1u16.to_le_bytes());
+            data.extend(value.iter().flat_map(|i| i.to_le_bytes()));
+            data.resize(align(data.len()), 0);
+        }
+        data.extend_from_slice(children);
+
+        let len = data.len() as u16;
+        data[0..2].copy_from_slice(&len.to_le_bytes());
+        data.resize(align(data.len()), 0);
+        data
+    }
+
+    /// Returns 32 bit bitmap icon with the transparency mask.
+    fn bitmap(size: u32) -> Vec<u8> {
+        let mut data = vec![0u8; 40];
+        data[0..4].copy_from_slice(&40u32.to_le_bytes());
+        data[4..8].copy_from_slice(&size.to_le_bytes());
+        data[8..12].copy_from_slice(&(size * 2).to_le_bytes());
+        data[12..14].copy_from_slice(&1u16.to_le_bytes());
+        data[14..16].copy_from_slice(&32u16.to_le_bytes());
+        data.resize(
+            40 + (size * size * 4 + size * size.div_ceil(32) * 4) as usize,
+            0xff,
+        );
+        data
+    }
+
+    fn icon_group(size: u8, len: usize) -> Vec<u8> {
+        let mut data = vec![0, 0, 1, 0, 1, 0, size, size, 0, 0, 1, 0, 32, 0];
+        data.extend((len as u32).to_le_bytes());
+        data.extend(1u16.to_le_bytes());
+        data
+    }
+
+    fn read(name: &str, icon: Vec<u8>) -> Pe {
+        let version = block(
+            "VS_VERSION_INFO",
+            None,
+            &block(
+                "StringFileInfo",
+                None,
+                &block(
+                    "040904b0",
+                    None,
+                    &[
+                        block("FileDescription", Some("Test application"), &[]),
+                        block("ProductName", Some("Test"), &[]),
+                    ]
+                    .concat(),
+                ),
+            ),
+        );
+        let section = resources(&[
+            (RT_ICON, 1, icon.clone()),
+            (RT_GROUP_ICON, 1, icon_group(128, icon.len())),
+            (RT_VERSION, 1, version),
+        ]);
+
+        let path =
+            std::env::temp_dir().join(format!("mkdsk-test-{}-{name}.exe", std::process::id()));
+        fs::write(&path, executable(&section)).unwrap();
+        assert!(is_pe(&path));
+        let pe = Pe::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        pe
+    }
+
+    #[test]
+    fn pe_reading() {
+        let pe = read("bitmap", bitmap(128));
+        assert_eq!(pe.get("ProductName"), Some("Test"));
+        assert_eq!(pe.get("FileDescription"), Some("Test application"));
+        let icon = image::load_from_memory(&pe.icon.unwrap()).unwrap();
+        assert_eq!((icon.width(), icon.height()), (128, 128));
+
+        // Broken icon does not discard the version information
+        let pe = read("broken", vec![0; 16]);
+        assert_eq!(pe.get("ProductName"), Some("Test"));
+        assert!(pe.icon.is_none());
+    }
+}
@@ -0,0 +1,44 @@
+//! # script
+//! Detects interpreters of the script sources that are not executable.
+
+use std::{fs::File, io::Read, path::Path};
+
+/// Script extensions and interpreters used if the script does not have a shebang.
+const INTERPRETERS: [(&str, &str); 5] = [
+    ("py", "python3"),
+    ("sh", "sh"),
+    ("bash", "bash"),
+    ("js", "node"),
+    ("rb", "ruby"),
+];
+
+/// Returns interpreter command of the script at `path`. Shebang is preferred over the file extension.
+/// Returns `None` if the file is not a script.
+pub fn script_interpreter(path: &Path) -> Option<String> {
+    if let Some(shebang) = shebang(path) {
+        return Some(shebang);
+    }
+
+    let ext = path.extension()?.to_str()?.to_lowercase();
+
+    INTERPRETERS
+        .into_iter()
+        .find(|(i, _)| *i == ext)
+        .map(|(_, interpreter)| interpreter.to_string())
+}
+
+/// Returns command of the script shebang, e.g. `/usr/bin/env python3` for `#!/usr/bin/env python3`.
+pub fn shebang(path: &Path) -> Option<String> {
+    let mut data = [0u8; 256];
+    let len = File::open(path).and_then(|mut i| i.read(&mut data)).ok()?;
+
+    let line = data[..len].split(|i| *i == b'\n').next()?;
+    let command = String::from_utf8_lossy(line.strip_prefix(b"#!")?)
+        .trim()
+        .to_string();
+
+    match command.is_empty() {
+        true => None,
+        false => Some(command),
+    }
+}
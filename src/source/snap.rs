@@ -0,0 +1,216 @@
+//! # snap
+//! Reads metadata of the installed snaps from their `meta` directory.
+
+use std::{fs, path::PathBuf};
+
+use super::apply_desktop_metadata;
+use crate::keyfile::KeyFile;
+use crate::shortcut::{EntryType, Shortcut};
+
+/// Directories where snaps are mounted. Distributions that do not allow `/snap` use `/var/lib/snapd/snap`.
+const SNAP_MOUNT_DIRS: [&str; 2] = ["/snap", "/var/lib/snapd/snap"];
+
+/// Installed snap.
+pub struct Snap {
+    name: String,
+    /// Directory of the current revision, e.g. `/snap/firefox/current`.
+    dir: PathBuf,
+    /// Name of the snap application, taken from the name of its `.desktop` file.
+    app: Option<String>,
+    desktop: Option<KeyFile>,
+    title: Option<String>,
+    summary: Option<String>,
+}
+
+impl Snap {
+    /// Looks for the installed snap with the given name. Returns `None` if `name` is not an installed snap.
+    pub fn find(name: &str) -> Option<Snap> {
+        let is_name = !name.is_empty()
+            && name
+                .chars()
+                .all(|i| i.is_ascii_lowercase() || i.is_ascii_digit() || i == '-');
+        if !is_name {
+            return None;
+        }
+
+        let dir = SNAP_MOUNT_DIRS
+            .into_iter()
+            .map(|i| PathBuf::from(i).join(name).join("current"))
+            .find(|i| i.join("meta/snap.yaml").is_file())?;
+
+        let yaml = fs::read_to_string(dir.join("meta/snap.yaml")).unwrap_or_default();
+
+        // Prefer the desktop file named after the snap, otherwise use the first one
+        let mut desktops: Vec<PathBuf> = fs::read_dir(dir.join("meta/gui"))
+            .map(|i| i.flatten().map(|i| i.path()).collect())
+            .unwrap_or_default();
+        desktops.retain(|i| i.extension().is_some_and(|ext| ext == "desktop"));
+        desktops.sort();
+        let desktop = desktops
+            .iter()
+            .find(|i| i.file_stem().is_some_and(|stem| stem == name))
+            .or(desktops.first());
+
+        Some(Snap {
+            name: name.to_string(),
+            app: desktop
+                .and_then(|i| i.file_stem())
+                .map(|i| i.to_string_lossy().to_string()),
+            desktop: desktop.and_then(|i| KeyFile::load(i)),
+            title: yaml_value(&yaml, "title"),
+            summary: yaml_value(&yaml, "summary"),
+            dir,
+        })
+    }
+
+    /// Fills `Shortcut` with the snap metadata. Entry runs the application with `snap run`.
+    pub fn apply(&self, shortcut: &mut Shortcut) {
+        shortcut.set_entry_type(EntryType::Application);
+        shortcut.set_exec(PathBuf::from("snap"));
+        shortcut.set_name(self.title.clone().unwrap_or(self.name.clone()));
+        if let Some(summary) = &self.summary {
+            shortcut.set_comment(summary.clone());
+        }
+
+        let mut args = match &self.app {
+            Some(app) if *app != self.name => format!("run {}.{app}", self.name),
+            _ => format!("run {}", self.name),
+        };
+
+        let mut icon = None;
+        if let Some(desktop) = &self.desktop {
+            apply_desktop_metadata(desktop, shortcut);
+
+            // Keep arguments of the packaged Exec, the program itself is started by snap
+            if let Some((_, exec_args)) = desktop
+                .get("Desktop Entry", "Exec")
+                .and_then(|i| i.split_once(' '))
+            {
+                args.push(' ');
+                args.push_str(exec_args.trim());
+            }
+
+            icon = desktop
+                .get("Desktop Entry", "Icon")
+                .map(|i| i.replace("${SNAP}", self.dir.to_str().unwrap()));
+        }
+
+        // Fall back to the icon packaged into meta/gui
+        if icon.is_none() {
+            icon = ["svg", "png"]
+                .into_iter()
+                .map(|ext| self.dir.join(format!("meta/gui/icon.{ext}")))
+                .find(|i| i.is_file())
+                .and_then(|i| i.to_str().map(String::from));
+        }
+        if let Some(icon) = icon {
+            shortcut.set_icon(icon);
+        }
+
+        shortcut.set_args(args);
+    }
+}
+
+/// Returns value of the top level `key` of the `snap.yaml`. Quoted values, comments and block scalars are supported,
+/// lines of the block scalars are joined with spaces.
+fn yaml_value(yaml: &str, key: &str) -> Option<String> {
+    let mut lines = yaml.lines();
+    let value = lines
+        .by_ref()
+        .filter(|i| !i.starts_with([' ', '\t']))
+        .filter_map(|i| i.split_once(':'))
+        .find(|(name, _)| name.trim() == key)
+        .map(|(_, value)| value.trim())?;
+
+    let value = match value.chars().next() {
+        Some('"') => {
+            let mut result = String::new();
+            let mut chars = value[1..].chars();
+            while let Some(i) = chars.next() {
+                match i {
+                    '"' => break,
+                    '\\' => match chars.next() {
+                        Some('n') => result.push('\n'),
+                        Some('t') => result.push('\t'),
+                        Some(val) => result.push(val),
+                        None => break,
+                    },
+                    _ => result.push(i),
+                }
+            }
+            result
+        }
+        Some('\'') => {
+            let mut result = String::new();
+            let mut chars = value[1..].chars().peekable();
+            while let Some(i) = chars.next() {
+                // Quote is escaped by another quote
+                match (i, chars.peek()) {
+                    ('\'', Some('\'')) => {
+                        chars.next();
+                        result.push('\'');
+                    }
+                    ('\'', _) => break,
+                    _ => result.push(i),
+                }
+            }
+            result
+        }
+        Some('|' | '>') => lines
+            .take_while(|i| i.trim().is_empty() || i.starts_with([' ', '\t']))
+            .map(str::trim)
+            .filter(|i| !i.is_empty())
+            .collect::<Vec<&str>>()
+            .join(" "),
+        // Comments start with `#` after a whitespace
+        _ => match value.find(" #") {
+            Some(pos) => value[..pos].trim().to_string(),
+            None if value.starts_with('#') => String::new(),
+            None => value.to_string(),
+        },
+    };
+
+    Some(value).filter(|i| !i.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn yaml_values() {
+        let yaml = r#"name: app # snap name
+version: '1.0'
+title: "Example \"App\""
+summary: 'It''s an app'
+apps:
+  app:
+    title: Nested
+    command: bin/app
+description: |
+  First line
+  second line: with colon
+plain: value#not comment
+empty: # comment
+"#;
+
+        assert_eq!(yaml_value(yaml, "name").as_deref(), Some("app"));
+        assert_eq!(yaml_value(yaml, "version").as_deref(), Some("1.0"));
+        assert_eq!(
+            yaml_value(yaml, "title").as_deref(),
+            Some("Example \"App\"")
+        );
+        assert_eq!(yaml_value(yaml, "summary").as_deref(), Some("It's an app"));
+        assert_eq!(
+            yaml_value(yaml, "description").as_deref(),
+            Some("First line second line: with colon")
+        );
+        assert_eq!(
+            yaml_value(yaml, "plain").as_deref(),
+            Some("value#not comment")
+        );
+        assert_eq!(yaml_value(yaml, "empty"), None);
+        assert_eq!(yaml_value(yaml, "apps"), None);
+        assert_eq!(yaml_value(yaml, "command"), None);
+    }
+}
@@ -0,0 +1,495 @@
+//! # squashfs
+//! Read-only access to files stored in a SquashFS 4.0 image. Used to read type 2 AppImages without mounting or executing them.
+//!
+//! See: https://dr-emann.github.io/squashfs/
+
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use flate2::read::ZlibDecoder;
+
+use super::resolve_link;
+
+const SQUASHFS_MAGIC: &[u8; 4] = b"hsqs";
+const SUPERBLOCK_SIZE: usize = 96;
+/// Maximum size of the uncompressed metadata block.
+const METADATA_BLOCK_SIZE: usize = 8192;
+/// Maximum number of symlinks followed while resolving a path.
+const MAX_SYMLINKS: usize = 16;
+/// Allowed sizes of the data blocks.
+const MIN_BLOCK_SIZE: u32 = 4096;
+const MAX_BLOCK_SIZE: u32 = 1 << 20;
+
+const COMPRESSION_GZIP: u16 = 1;
+const COMPRESSION_XZ: u16 = 4;
+const COMPRESSION_ZSTD: u16 = 6;
+
+const INODE_DIR: u16 = 1;
+const INODE_FILE: u16 = 2;
+const INODE_SYMLINK: u16 = 3;
+const INODE_EXT_DIR: u16 = 8;
+const INODE_EXT_FILE: u16 = 9;
+const INODE_EXT_SYMLINK: u16 = 10;
+
+/// Fragment index of files that do not have a fragment.
+const NO_FRAGMENT: u32 = 0xFFFFFFFF;
+/// Bit of the block size that indicates that the block is stored uncompressed.
+const DATA_UNCOMPRESSED: u32 = 1 << 24;
+/// Bit of the metadata block header that indicates that the block is stored uncompressed.
+const METADATA_UNCOMPRESSED: u16 = 1 << 15;
+
+enum Inode {
+    Dir {
+        block: u32,
+        offset: u16,
+        size: u32,
+    },
+    File {
+        blocks_start: u64,
+        size: u64,
+        fragment: u32,
+        fragment_offset: u32,
+        block_sizes: Vec<u32>,
+    },
+    Symlink(String),
+}
+
+/// SquashFS image opened for reading.
+pub struct SquashFs {
+    file: File,
+    /// Offset of the image in the file.
+    start: u64,
+    compression: u16,
+    block_size: u32,
+    root_inode: u64,
+    inode_table: u64,
+    directory_table: u64,
+    fragment_table: u64,
+}
+
+impl SquashFs {
+    /// Opens SquashFS image that starts at `start` bytes of the file.
+    pub fn open(path: &Path, start: u64) -> Result<SquashFs, String> {
+        let mut file = File::open(path).map_err(|e| e.to_string())?;
+
+        let mut sb = [0u8; SUPERBLOCK_SIZE];
+        file.seek(SeekFrom::Start(start))
+            .and_then(|_| file.read_exact(&mut sb))
+            .map_err(|e| e.to_string())?;
+
+        if &sb[0..4] != SQUASHFS_MAGIC || u16_at(&sb, 28) != 4 {
+            return Err(String::from("not a SquashFS 4.0 image"));
+        }
+
+        let compression = u16_at(&sb, 20);
+        if ![COMPRESSION_GZIP, COMPRESSION_XZ, COMPRESSION_ZSTD].contains(&compression) {
+            return Err(format!("unsupported SquashFS compression {compression}"));
+        }
+
+        let block_size = u32_at(&sb, 12);
+        if !block_size.is_power_of_two() || !(MIN_BLOCK_SIZE..=MAX_BLOCK_SIZE).contains(&block_size)
+        {
+            return Err(format!("invalid SquashFS block size {block_size}"));
+        }
+
+        Ok(SquashFs {
+            file,
+            start,
+            compression,
+            block_size,
+            root_inode: u64_at(&sb, 32),
+            inode_table: u64_at(&sb, 64),
+            directory_table: u64_at(&sb, 72),
+            fragment_table: u64_at(&sb, 80),
+        })
+    }
+
+    /// Returns names of the files in the root directory.
+    pub fn list_root(&mut self) -> Result<Vec<String>, String> {
+        let root = self.read_inode(self.root_inode)?;
+
+        Ok(self
+            .read_dir(&root)?
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect())
+    }
+
+    /// Reads file content. Symlinks are followed, `path` is relative to the image root.
+    pub fn read_file(&mut self, path: &str) -> Result<Vec<u8>, String> {
+        let mut path = path.to_string();
+
+        for _ in 0..MAX_SYMLINKS {
+            match self.lookup(&path)? {
+                Inode::Symlink(target) => {
+                    path = resolve_link(&path, &target);
+                }
+                inode @ Inode::File { .. } => return self.read_data(&inode),
+                Inode::Dir { .. } => return Err(format!("{path} is a directory")),
+            }
+        }
+
+        Err(format!("{path}: too many levels of symbolic links"))
+    }
+
+    /// Returns inode of the `path` without following the last symlink.
+    fn lookup(&mut self, path: &str) -> Result<Inode, String> {
+        let mut inode = self.read_inode(self.root_inode)?;
+
+        for name in path.split('/').filter(|i| !i.is_empty() && *i != ".") {
+            let entry = self
+                .read_dir(&inode)?
+                .into_iter()
+                .find(|(i, _)| i == name)
+                .ok_or(format!("{path}: no such file"))?;
+
+            inode = self.read_inode(entry.1)?;
+        }
+
+        Ok(inode)
+    }
+
+    fn read_inode(&mut self, inode_ref: u64) -> Result<Inode, String> {
+        let block = self.inode_table + (inode_ref >> 16);
+        let offset = (inode_ref & 0xFFFF) as usize;
+
+        let header = self.read_metadata(block, offset, 16)?;
+        let kind = u16_at(&header, 0);
+
+        // Size of the fixed part of the inode
+        let size = match kind {
+            INODE_DIR | INODE_FILE => 32,
+            INODE_EXT_DIR => 40,
+            INODE_EXT_FILE => 56,
+            INODE_SYMLINK | INODE_EXT_SYMLINK => 24,
+            _ => return Err(format!("unsupported SquashFS inode type {kind}")),
+        };
+        let data = self.read_metadata(block, offset, size)?;
+
+        match kind {
+            INODE_DIR => Ok(Inode::Dir {
+                block: u32_at(&data, 16),
+                size: u16_at(&data, 24) as u32,
+                offset: u16_at(&data, 26),
+            }),
+            INODE_EXT_DIR => Ok(Inode::Dir {
+                size: u32_at(&data, 20),
+                block: u32_at(&data, 24),
+                offset: u16_at(&data, 34),
+            }),
+            kind @ (INODE_FILE | INODE_EXT_FILE) => {
+                let (blocks_start, size, fragment, fragment_offset, inode_size) = match kind {
+                    INODE_FILE => (
+                        u32_at(&data, 16) as u64,
+                        u32_at(&data, 28) as u64,
+                        u32_at(&data, 20),
+                        u32_at(&data, 24),
+                        32,
+                    ),
+                    _ => (
+                        u64_at(&data, 16),
+                        u64_at(&data, 24),
+                        u32_at(&data, 44),
+                        u32_at(&data, 48),
+                        56,
+                    ),
+                };
+
+                let mut count = size / self.block_size as u64;
+                if fragment == NO_FRAGMENT && size % self.block_size as u64 != 0 {
+                    count += 1;
+                }
+
+                let sizes = self.read_metadata(block, offset + inode_size, count as usize * 4)?;
+                let block_sizes = (0..count as usize).map(|i| u32_at(&sizes, i * 4)).collect();
+
+                Ok(Inode::File {
+                    blocks_start,
+                    size,
+                    fragment,
+                    fragment_offset,
+                    block_sizes,
+                })
+            }
+            INODE_SYMLINK | INODE_EXT_SYMLINK => {
+                let size = u32_at(&data, 20) as usize;
+                let target = self.read_metadata(block, offset + 24, size)?;
+
+                Ok(Inode::Symlink(String::from_utf8_lossy(&target).to_string()))
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Returns names and inode references of the directory entries.
+    fn read_dir(&mut self, inode: &Inode) -> Result<Vec<(String, u64)>, String> {
+        let (block, offset, size) = match inode {
+            Inode::Dir {
+                block,
+                offset,
+                size,
+            } => (*block, *offset, *size),
+            _ => return Err(String::from("not a directory")),
+        };
+
+        // Directory size includes 3 bytes for the implicit "." and ".." entries
+        let size = (size as usize).saturating_sub(3);
+        let data =
+            self.read_metadata(self.directory_table + block as u64, offset as usize, size)?;
+
+        let mut entries = Vec::new();
+        let mut pos = 0;
+        while pos + 12 <= data.len() {
+            let count = u32_at(&data, pos) as usize + 1;
+            let start = u32_at(&data, pos + 4) as u64;
+            pos += 12;
+
+            for _ in 0..count {
+                if pos + 8 > data.len() {
+                    break;
+                }
+                let offset = u16_at(&data, pos) as u64;
+                let name_size = u16_at(&data, pos + 6) as usize + 1;
+                let name = match data.get(pos + 8..pos + 8 + name_size) {
+                    Some(val) => String::from_utf8_lossy(val).to_string(),
+                    None => break,
+                };
+                pos += 8 + name_size;
+
+                entries.push((name, (start << 16) | offset));
+            }
+        }
+
+        Ok(entries)
+    }
+
+    fn read_data(&mut self, inode: &Inode) -> Result<Vec<u8>, String> {
+        let (blocks_start, size, fragment, fragment_offset, block_sizes) = match inode {
+            Inode::File {
+                blocks_start,
+                size,
+                fragment,
+                fragment_offset,
+                block_sizes,
+            } => (
+                *blocks_start,
+                *size,
+                *fragment,
+                *fragment_offset,
+                block_sizes,
+            ),
+            _ => return Err(String::from("not a file")),
+        };
+
+        let mut data = Vec::with_capacity(size as usize);
+        let mut pos = blocks_start;
+        for block_size in block_sizes {
+            let stored = block_size & !DATA_UNCOMPRESSED;
+
+            if stored == 0 {
+                // Sparse block
+                data.resize(data.len() + self.block_size as usize, 0);
+                continue;
+            }
+
+            let raw = self.read_raw(pos, stored as usize)?;
+            pos += stored as u64;
+
+            if block_size & DATA_UNCOMPRESSED != 0 {
+                data.extend(raw);
+            } else {
+                data.extend(self.decompress(&raw)?);
+            }
+        }
+
+        if fragment != NO_FRAGMENT {
+            // Fragment table is a list of pointers to metadata blocks, each containing 512 entries of 16 bytes
+            let pointer = self.read_raw(self.fragment_table + (fragment as u64 / 512) * 8, 8)?;
+            let entry =
+                self.read_metadata(u64_at(&pointer, 0), (fragment as usize % 512) * 16, 16)?;
+            let start = u64_at(&entry, 0);
+            let block_size = u32_at(&entry, 8);
+
+            let raw = self.read_raw(start, (block_size & !DATA_UNCOMPRESSED) as usize)?;
+            let block = match block_size & DATA_UNCOMPRESSED {
+                0 => self.decompress(&raw)?,
+                _ => raw,
+            };
+
+            let tail = (size % self.block_size as u64) as usize;
+            let begin = fragment_offset as usize;
+            match block.get(begin..begin + tail) {
+                Some(val) => data.extend_from_slice(val),
+                None => return Err(String::from("invalid SquashFS fragment")),
+            }
+        }
+
+        data.truncate(size as usize);
+        Ok(data)
+    }
+
+    /// Reads `len` bytes from the metadata blocks starting at `offset` of the uncompressed block at `block`.
+    fn read_metadata(&mut self, block: u64, offset: usize, len: usize) -> Result<Vec<u8>, String> {
+        let mut data = Vec::new();
+        let mut pos = block;
+
+        while data.len() < offset + len {
+            let header = self.read_raw(pos, 2)?;
+            let header = u16_at(&header, 0);
+            let size = (header & !METADATA_UNCOMPRESSED) as usize;
+
+            let raw = self.read_raw(pos + 2, size)?;
+            pos += 2 + size as u64;
+
+            let block = match header & METADATA_UNCOMPRESSED {
+                0 => self.decompress(&raw)?,
+                _ => raw,
+            };
+            if block.is_empty() || block.len() > METADATA_BLOCK_SIZE {
+                return Err(String::from("invalid SquashFS metadata block"));
+            }
+
+            data.extend(block);
+        }
+
+        Ok(data[offset..offset + len].to_vec())
+    }
+
+    /// Reads `len` bytes at `pos` relative to the image start.
+    fn read_raw(&mut self, pos: u64, len: usize) -> Result<Vec<u8>, String> {
+        let mut data = vec![0u8; len];
+
+        self.file
+            .seek(SeekFrom::Start(self.start + pos))
+            .and_then(|_| self.file.read_exact(&mut data))
+            .map_err(|e| e.to_string())?;
+
+        Ok(data)
+    }
+
+    fn decompress(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        let mut out = Vec::new();
+
+        let result = match self.compression {
+            COMPRESSION_GZIP => ZlibDecoder::new(data).read_to_end(&mut out).map(|_| ()),
+            COMPRESSION_XZ => lzma_rs::xz_decompress(&mut &data[..], &mut out)
+                .map_err(|e| std::io::Error::other(e.to_string())),
+            _ => ruzstd::StreamingDecoder::new(data)
+                .map_err(|e| std::io::Error::other(e.to_string()))
+                .and_then(|mut i| i.read_to_end(&mut out))
+                .map(|_| ()),
+        };
+
+        match result {
+            Ok(_) => Ok(out),
+            Err(e) => Err(format!("SquashFS decompression failed: {e}")),
+        }
+    }
+}
+
+fn u16_at(data: &[u8], pos: usize) -> u16 {
+    u16::from_le_bytes(data[pos..pos + 2].try_into().unwrap())
+}
+fn u32_at(data: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes(data[pos..pos + 4].try_into().unwrap())
+}
+fn u64_at(data: &[u8], pos: usize) -> u64 {
+    u64::from_le_bytes(data[pos..pos + 8].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns uncompressed metadata block.
+    fn metadata(data: &[u8]) -> Vec<u8> {
+        let mut block = (data.len() as u16 | METADATA_UNCOMPRESSED)
+            .to_le_bytes()
+            .to_vec();
+        block.extend_from_slice(data);
+        block
+    }
+
+    fn inode(kind: u16, fields: &[&[u8]]) -> Vec<u8> {
+        let mut data = kind.to_le_bytes().to_vec();
+        data.resize(16, 0);
+        data.extend(fields.concat());
+        data
+    }
+
+    fn image(block_size: u32) -> Vec<u8> {
+        let content = b"hello";
+        let data_start = SUPERBLOCK_SIZE as u32;
+        let inode_table = data_start + content.len() as u32;
+
+        // Root directory, file and symlink to the file
+        let dir_entries = |size: u16| {
+            inode(
+                INODE_DIR,
+                &[
+                    &0u32.to_le_bytes(),
+                    &2u32.to_le_bytes(),
+                    &size.to_le_bytes(),
+                    &[0; 6],
+                ],
+            )
+        };
+        let file = inode(
+            INODE_FILE,
+            &[
+                &data_start.to_le_bytes(),
+                &NO_FRAGMENT.to_le_bytes(),
+                &0u32.to_le_bytes(),
+                &(content.len() as u32).to_le_bytes(),
+                &(content.len() as u32 | DATA_UNCOMPRESSED).to_le_bytes(),
+            ],
+        );
+        let link = inode(
+            INODE_SYMLINK,
+            &[&1u32.to_le_bytes(), &9u32.to_le_bytes(), b"hello.txt"],
+        );
+
+        let mut dir = [1u32.to_le_bytes(), 0u32.to_le_bytes(), 1u32.to_le_bytes()].concat();
+        let file_offset = dir_entries(0).len() as u16;
+        let link_offset = file_offset + file.len() as u16;
+        for (offset, name) in [(file_offset, "hello.txt"), (link_offset, "link")] {
+            dir.extend(offset.to_le_bytes());
+            dir.extend([0; 4]);
+            dir.extend((name.len() as u16 - 1).to_le_bytes());
+            dir.extend(name.as_bytes());
+        }
+
+        let inodes = metadata(&[dir_entries(dir.len() as u16 + 3), file, link].concat());
+        let directory_table = inode_table + inodes.len() as u32;
+
+        let mut sb = [0u8; SUPERBLOCK_SIZE];
+        sb[0..4].copy_from_slice(SQUASHFS_MAGIC);
+        sb[12..16].copy_from_slice(&block_size.to_le_bytes());
+        sb[20..22].copy_from_slice(&COMPRESSION_GZIP.to_le_bytes());
+        sb[28..30].copy_from_slice(&4u16.to_le_bytes());
+        sb[64..72].copy_from_slice(&(inode_table as u64).to_le_bytes());
+        sb[72..80].copy_from_slice(&(directory_table as u64).to_le_bytes());
+
+        [sb.to_vec(), content.to_vec(), inodes, metadata(&dir)].concat()
+    }
+
+    #[test]
+    fn squashfs_reading() {
+        let path = std::env::temp_dir().join(format!("mkdsk-test-{}.sqfs", std::process::id()));
+
+        std::fs::write(&path, image(4096)).unwrap();
+        let mut squashfs = SquashFs::open(&path, 0).unwrap();
+        assert_eq!(squashfs.list_root().unwrap(), ["hello.txt", "link"]);
+        assert_eq!(squashfs.read_file("hello.txt").unwrap(), b"hello");
+        assert_eq!(squashfs.read_file("link").unwrap(), b"hello");
+        assert!(squashfs.read_file("missing").is_err());
+
+        std::fs::write(&path, image(0)).unwrap();
+        assert!(SquashFs::open(&path, 0).is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,130 @@
+//! # zip
+//! Reads single files stored in a ZIP archive. Used to read manifests of the `.jar` files.
+//!
+//! See: https://pkware.cachefly.net/webdocs/casestudies/APPNOTE.TXT
+
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use flate2::read::DeflateDecoder;
+
+const EOCD_MAGIC: &[u8; 4] = b"PK\x05\x06";
+const CENTRAL_MAGIC: &[u8; 4] = b"PK\x01\x02";
+const LOCAL_MAGIC: &[u8; 4] = b"PK\x03\x04";
+const EOCD_SIZE: usize = 22;
+const CENTRAL_HEADER_SIZE: usize = 46;
+const LOCAL_HEADER_SIZE: usize = 30;
+/// End of central directory record may be followed by a comment of up to 65535 bytes.
+const MAX_COMMENT_SIZE: u64 = 65535;
+
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATED: u16 = 8;
+
+/// Reads content of the file `name` stored in the ZIP archive. Names are compared case-insensitively.
+/// Returns `Ok(None)` if the archive does not contain the file.
+pub fn read_zip_file(path: &Path, name: &str) -> Result<Option<Vec<u8>>, String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let len = file.metadata().map_err(|e| e.to_string())?.len();
+
+    // Find end of central directory record
+    let tail_len = len.min(MAX_COMMENT_SIZE + EOCD_SIZE as u64);
+    let mut tail = vec![0u8; tail_len as usize];
+    file.seek(SeekFrom::Start(len - tail_len))
+        .and_then(|_| file.read_exact(&mut tail))
+        .map_err(|e| e.to_string())?;
+
+    let eocd = (0..tail.len().saturating_sub(EOCD_SIZE - 1))
+        .rev()
+        .find(|i| &tail[*i..*i + 4] == EOCD_MAGIC)
+        .ok_or(String::from("not a ZIP archive"))?;
+    let eocd = &tail[eocd..];
+
+    let entries = u16_at(eocd, 10);
+    let central_size = u32_at(eocd, 12);
+    let central_offset = u32_at(eocd, 16);
+    if entries == u16::MAX || central_offset == u32::MAX {
+        return Err(String::from("ZIP64 archives are not supported"));
+    }
+
+    let mut central = vec![0u8; central_size as usize];
+    file.seek(SeekFrom::Start(central_offset as u64))
+        .and_then(|_| file.read_exact(&mut central))
+        .map_err(|e| e.to_string())?;
+
+    let mut pos = 0;
+    for _ in 0..entries {
+        if central.len() < pos + CENTRAL_HEADER_SIZE || &central[pos..pos + 4] != CENTRAL_MAGIC {
+            return Err(String::from("corrupted ZIP central directory"));
+        }
+
+        let header = &central[pos..];
+        let name_len = u16_at(header, 28) as usize;
+        let entry_name = header
+            .get(CENTRAL_HEADER_SIZE..CENTRAL_HEADER_SIZE + name_len)
+            .ok_or(String::from("corrupted ZIP central directory"))?;
+
+        if String::from_utf8_lossy(entry_name).eq_ignore_ascii_case(name) {
+            return read_entry(
+                &mut file,
+                u16_at(header, 10),
+                u32_at(header, 20),
+                u32_at(header, 24),
+                u32_at(header, 42),
+            )
+            .map(Some);
+        }
+
+        pos += CENTRAL_HEADER_SIZE
+            + name_len
+            + u16_at(header, 30) as usize
+            + u16_at(header, 32) as usize;
+    }
+
+    Ok(None)
+}
+
+/// Reads and decompresses file data that follows the local header at `offset`.
+fn read_entry(
+    file: &mut File,
+    method: u16,
+    compressed_size: u32,
+    size: u32,
+    offset: u32,
+) -> Result<Vec<u8>, String> {
+    let mut header = [0u8; LOCAL_HEADER_SIZE];
+    file.seek(SeekFrom::Start(offset as u64))
+        .and_then(|_| file.read_exact(&mut header))
+        .map_err(|e| e.to_string())?;
+    if &header[0..4] != LOCAL_MAGIC {
+        return Err(String::from("corrupted ZIP local header"));
+    }
+
+    // Local name and extra field may differ from the ones in the central directory
+    let skip = u16_at(&header, 26) as i64 + u16_at(&header, 28) as i64;
+    let mut data = vec![0u8; compressed_size as usize];
+    file.seek(SeekFrom::Current(skip))
+        .and_then(|_| file.read_exact(&mut data))
+        .map_err(|e| e.to_string())?;
+
+    match method {
+        METHOD_STORED => Ok(data),
+        METHOD_DEFLATED => {
+            let mut out = Vec::with_capacity(size as usize);
+            DeflateDecoder::new(data.as_slice())
+                .read_to_end(&mut out)
+                .map_err(|e| e.to_string())?;
+            Ok(out)
+        }
+        _ => Err(format!("unsupported ZIP compression method {method}")),
+    }
+}
+
+fn u16_at(data: &[u8], pos: usize) -> u16 {
+    u16::from_le_bytes(data[pos..pos + 2].try_into().unwrap())
+}
+fn u32_at(data: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes(data[pos..pos + 4].try_into().unwrap())
+}
@@ -0,0 +1,138 @@
+//! # webapp
+//! Turns Link entries into Application entries that open the URL in a separate browser window.
+//!
+//! Chromium based browsers use the app mode. Firefox does not have one, so it is started with a dedicated profile.
+
+use std::path::PathBuf;
+
+use crate::settings::WEBAPP_PROFILE_DIR;
+use crate::shortcut::{quote_exec, EntryType, Shortcut};
+use crate::xdg::data_home;
+
+enum Browser {
+    Chromium,
+    Firefox,
+}
+
+/// Browser executables in the order of preference.
+const BROWSERS: [(&str, Browser); 10] = [
+    ("chromium", Browser::Chromium),
+    ("chromium-browser", Browser::Chromium),
+    ("google-chrome", Browser::Chromium),
+    ("google-chrome-stable", Browser::Chromium),
+    ("brave-browser", Browser::Chromium),
+    ("brave", Browser::Chromium),
+    ("microsoft-edge", Browser::Chromium),
+    ("microsoft-edge-stable", Browser::Chromium),
+    ("firefox", Browser::Firefox),
+    ("firefox-esr", Browser::Firefox),
+];
+
+/// Makes `shortcut` run the first installed browser with its URL in a separate window.
+/// Window class is derived from the URL, so each web app has its own window group.
+/// Returns profile directory that must be created before the entry is used.
+pub fn apply_webapp(shortcut: &mut Shortcut) -> Result<Option<PathBuf>, String> {
+    if *shortcut.get_entry_type() != EntryType::Link {
+        return Err(String::from("--webapp can only be used with URL sources"));
+    }
+
+    let (exec, browser) = BROWSERS
+        .into_iter()
+        .find(|(i, _)| which::which(i).is_ok())
+        .ok_or(String::from("no supported browser found"))?;
+
+    let url = shortcut.get_url().to_string();
+    let class = webapp_class(&url);
+    let (args, profile) = browser_args(&browser, &url, &class);
+
+    shortcut.set_entry_type(EntryType::Application);
+    shortcut.set_exec(PathBuf::from(exec));
+    shortcut.set_args(args);
+    shortcut.set_startup_wm_class(class);
+
+    Ok(profile)
+}
+
+/// Returns `Exec` arguments that open `url` in a separate window of the `browser` with the window `class` and the
+/// profile directory the browser needs.
+fn browser_args(browser: &Browser, url: &str, class: &str) -> (String, Option<PathBuf>) {
+    let mut profile = None;
+    let args = match browser {
+        Browser::Chromium => vec![format!("--app={url}"), format!("--class={class}")],
+        Browser::Firefox => {
+            let dir = data_home().join(WEBAPP_PROFILE_DIR).join(class);
+            profile = Some(dir.clone());
+
+            vec![
+                String::from("--new-instance"),
+                String::from("--profile"),
+                dir.to_str().unwrap().to_string(),
+                String::from("--class"),
+                class.to_string(),
+                String::from("--name"),
+                class.to_string(),
+                url.to_string(),
+            ]
+        }
+    };
+    let args: Vec<String> = args.iter().map(|i| quote_exec(i)).collect();
+
+    (args.join(" "), profile)
+}
+
+/// Returns window class of the web app, e.g. `webapp-example.com-tools` for `https://example.com/tools/`.
+fn webapp_class(url: &str) -> String {
+    let url = url.split_once("://").map(|(_, i)| i).unwrap_or(url);
+    let url = url.split(['?', '#']).next().unwrap_or_default();
+
+    let class: String = url
+        .trim_end_matches('/')
+        .chars()
+        .map(
+            |i| match i.is_ascii_alphanumeric() || i == '.' || i == '-' {
+                true => i.to_ascii_lowercase(),
+                false => '-',
+            },
+        )
+        .collect();
+
+    format!("webapp-{class}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_class() {
+        assert_eq!(webapp_class("https://example.com/"), "webapp-example.com");
+        assert_eq!(
+            webapp_class("https://Example.com:8080/Tools/app?x=1#top"),
+            "webapp-example.com-8080-tools-app"
+        );
+        assert_eq!(webapp_class("example.com/a_b"), "webapp-example.com-a-b");
+
+        let url = "https://example.com/a b";
+        let class = webapp_class(url);
+        assert_eq!(class, "webapp-example.com-a-b");
+
+        let (args, profile) = browser_args(&Browser::Chromium, url, &class);
+        assert_eq!(
+            args,
+            "\"--app=https://example.com/a b\" --class=webapp-example.com-a-b"
+        );
+        assert_eq!(profile, None);
+
+        // Firefox sets the class of the window with --class and --name, each web app has its own profile
+        let (args, profile) = browser_args(&Browser::Firefox, url, &class);
+        let profile = profile.unwrap();
+        assert!(profile.ends_with(PathBuf::from(WEBAPP_PROFILE_DIR).join(&class)));
+        assert_eq!(
+            args,
+            format!(
+                "--new-instance --profile {} --class {class} --name {class} \"{url}\"",
+                quote_exec(profile.to_str().unwrap())
+            )
+        );
+    }
+}
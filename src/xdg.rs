@@ -0,0 +1,38 @@
+//! # xdg
+//! Contains functions that resolve XDG base directories.
+
+use std::{env, path::PathBuf};
+
+/// Returns user's home directory.
+pub fn home_dir() -> PathBuf {
+    PathBuf::from(env::var("HOME").unwrap_or_default())
+}
+
+/// Returns `$XDG_DATA_HOME`. Falls back to `~/.local/share` if the variable is not set or is not an absolute path.
+pub fn data_home() -> PathBuf {
+    match env::var("XDG_DATA_HOME") {
+        Ok(val) if PathBuf::from(&val).is_absolute() => PathBuf::from(val),
+        _ => home_dir().join(".local/share"),
+    }
+}
+
+/// Returns `$XDG_CONFIG_HOME`. Falls back to `~/.config` if the variable is not set or is not an absolute path.
+pub fn config_home() -> PathBuf {
+    match env::var("XDG_CONFIG_HOME") {
+        Ok(val) if PathBuf::from(&val).is_absolute() => PathBuf::from(val),
+        _ => home_dir().join(".config"),
+    }
+}
+
+/// Returns `$XDG_DATA_DIRS`. Falls back to `/usr/local/share:/usr/share` if the variable is not set or empty.
+pub fn data_dirs() -> Vec<PathBuf> {
+    let dirs = match env::var("XDG_DATA_DIRS") {
+        Ok(val) if !val.is_empty() => val,
+        _ => String::from("/usr/local/share:/usr/share"),
+    };
+
+    dirs.split(':')
+        .map(PathBuf::from)
+        .filter(|i| i.is_absolute())
+        .collect()
+}